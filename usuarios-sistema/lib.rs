@@ -2,9 +2,10 @@
 
 #[ink::contract]
 mod usuarios_sistema {
-    use ink::prelude::{format, string::String};
+    use ink::prelude::{string::String, vec::Vec};
     use ink::storage::Mapping;    
-    use ink::storage::StorageVec;
+    use ink::env::hash::{Blake2x256, HashOutput};
+    use ink::scale::Encode;
 
     #[ink(storage)]
 
@@ -15,6 +16,126 @@ mod usuarios_sistema {
         value: bool, //Tendríamos que dejar el value??
         usuarios: ink::storage::Mapping<AccountId, Usuario>,
         //historial_transacciones: ink::storage::StorageVec<transaccion>, //-> Hay que tener un struct para transaccion???
+        admin: AccountId, //Quien despliega el contrato administra las categorías.
+        categorias: Mapping<u32, Categoria>,
+        cantidad_categorias: u32,
+        publicaciones: Mapping<u32, Publicacion>,
+        cantidad_publicaciones: u32,
+        perfiles_vendedor: Mapping<AccountId, PerfilVendedor>,
+        direcciones: Mapping<AccountId, Vec<Direccion>>, //Libreta de direcciones de envío de cada comprador.
+        direccion_predeterminada: Mapping<AccountId, u32>,
+        nonces: Mapping<AccountId, u64>, //Próximo nonce válido para registros firmados (evita que se reutilice una firma).
+        verificadores: Mapping<AccountId, ()>, //Cuentas que el admin habilitó para verificar identidades (KYC).
+        umbral_verificacion: Option<ReglaVerificacion>, //Si es None, cualquier vendedor puede publicar sin verificarse.
+        valor_publicado: Mapping<AccountId, Balance>, //Suma de precio * stock de las publicaciones de cada vendedor.
+        tarifa_registro: Balance, //Depósito mínimo para registrarse (0 = registro gratuito).
+        modo_registro: ModoRegistro,
+        invitaciones: Mapping<Hash, (AccountId, u32)>, //Hash del código de invitación -> (quien la emitió, número). Se borra al usarse.
+        codigos_invitacion: Mapping<u32, Hash>, //Número de invitación -> hash, para recorrer las de cada emisor.
+        cantidad_invitaciones: u32,
+        registros_pendientes: Mapping<AccountId, Usuario>,
+        permisos: Mapping<Rol, Vec<Permiso>>, //Tabla de qué acciones habilita cada rol.
+        solicitudes_vendedor: Mapping<AccountId, SolicitudVendedor>,
+        historial: Mapping<(AccountId, u32), Cambio>, //Historial de cambios de cada usuario. Solo se agregan entradas.
+        cantidad_cambios: Mapping<AccountId, u32>,
+        periodo_inactividad: Option<Timestamp>, //A partir de cuánto tiempo sin actividad se puede limpiar una cuenta (None = nunca).
+        recompensa_limpieza: Balance, //Lo que se le paga, del depósito liberado, a quien limpia una cuenta.
+        saldos: Mapping<AccountId, Balance>, //Pagos que no se pudieron transferir; su dueño los retira con `retirar_saldo`.
+        migraciones_pendientes: Mapping<AccountId, AccountId>, //Cuenta nueva -> cuenta vieja que pidió migrarse a ella.
+        guardianes: Mapping<AccountId, Guardianes>,
+        recuperaciones: Mapping<AccountId, Recuperacion>, //Cuenta a recuperar -> pedido de recuperación en curso.
+        ventana_recuperacion: Timestamp, //Tiempo que tienen los guardianes para juntar las aprobaciones.
+        plazo_cancelacion: Timestamp, //Tiempo que tiene el dueño para cancelar una recuperación ya aprobada.
+        handles: Mapping<String, AccountId>, //Índice inverso: handle -> dueño.
+        handles_reservados: Mapping<String, ()>,
+        favoritos: Mapping<AccountId, Vec<u32>>, //Puede tener productos ya retirados; se limpian al modificar la lista.
+        guardados: Mapping<(u32, AccountId), ()>, //(producto, cuenta) si la cuenta guardó el producto.
+        contador_favoritos: Mapping<u32, u32>, //Cuántas cuentas guardaron cada producto.
+        reportes: Mapping<u32, Reporte>,
+        cantidad_reportes: u32,
+        reportantes: Mapping<(ObjetoReporte, AccountId), ()>, //(objeto, cuenta) si la cuenta reportó la publicación o el usuario.
+        contador_reportes: Mapping<ObjetoReporte, u32>, //Reportes que cuentan para ocultar cada objeto.
+        umbral_reportes: u32, //Reportes de cuentas distintas que ocultan una publicación automáticamente.
+        advertencias: Mapping<AccountId, u32>,
+        suspendidos: Mapping<AccountId, ()>, //Vendedores que no pueden publicar y cuyas publicaciones no se listan.
+        tesoreria: Balance, //Fondos de la plataforma, separados de los depósitos de los usuarios.
+        precio_promocion: Balance, //Precio mínimo por unidad de tiempo promocionada (0 = promociones deshabilitadas).
+        unidad_promocion: Timestamp,
+        promociones: Mapping<u32, Promocion>,
+        elementos_lista: Mapping<(Lista, u32), AccountId>, //Listas de cuentas: (lista, posición) -> cuenta.
+        posiciones_lista: Mapping<(Lista, AccountId), u32>,
+        elementos_lista_ids: Mapping<(Lista, u32), u32>, //Listas de ids: (lista, posición) -> id.
+        posiciones_lista_ids: Mapping<(Lista, u32), u32>, //(lista, id) -> posición.
+        largo_lista: Mapping<Lista, u32>, //Compartido por las listas de cuentas y las de ids.
+    }
+
+    /// Se emite por cada cuenta eliminada en `limpiar_inactivos`.
+    #[ink(event)]
+    pub struct UsuarioEliminado {
+        #[ink(topic)]
+        cuenta: AccountId,
+        limpiador: AccountId,
+        recompensa: Balance,
+    }
+
+    /// Se emite al crear una publicación, para que los servicios externos avisen a los seguidores del vendedor.
+    #[ink(event)]
+    pub struct NuevaPublicacion {
+        #[ink(topic)]
+        vendedor: AccountId,
+        id: u32,
+    }
+
+    #[ink(event)]
+    pub struct ReporteCreado {
+        id: u32,
+        #[ink(topic)]
+        objeto: ObjetoReporte,
+        motivo: MotivoReporte,
+    }
+
+    #[ink(event)]
+    pub struct ReporteCerrado {
+        #[ink(topic)]
+        id: u32,
+        moderador: AccountId,
+    }
+
+    /// `moderador` es `None` cuando se ocultó automáticamente por cantidad de reportes.
+    #[ink(event)]
+    pub struct PublicacionOcultada {
+        #[ink(topic)]
+        id: u32,
+        moderador: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct PublicacionRestaurada {
+        #[ink(topic)]
+        id: u32,
+        moderador: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct UsuarioAdvertido {
+        #[ink(topic)]
+        cuenta: AccountId,
+        moderador: AccountId,
+        motivo: MotivoReporte,
+    }
+
+    #[ink(event)]
+    pub struct VendedorSuspendido {
+        #[ink(topic)]
+        cuenta: AccountId,
+        moderador: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct VendedorReactivado {
+        #[ink(topic)]
+        cuenta: AccountId,
+        moderador: AccountId,
     }
 
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
@@ -28,6 +149,53 @@ mod usuarios_sistema {
         UsuarioYaRegistrado,
         UsuarioNoExiste,
         RolYaEnUso,
+        NoEsAdmin,
+        NoEsVendedor,
+        CategoriaNoExiste,
+        PublicacionNoExiste,
+        DatosInvalidos,
+        Desbordamiento,
+        PerfilNoExiste,
+        NoEsComprador,
+        DireccionNoExiste,
+        FirmaInvalida,
+        FirmaVencida,
+        NonceInvalido,
+        NoEsVerificador,
+        VerificacionRequerida,
+        PagoInsuficiente,
+        TienePublicaciones,
+        TransferenciaFallida,
+        InvitacionRequerida,
+        InvitacionInvalida,
+        RegistroPendiente,
+        SolicitudNoExiste,
+        NoEsModerador,
+        RolNoPermitido,
+        MigracionNoExiste,
+        CuentaEnUso,
+        NoEsGuardian,
+        RecuperacionNoExiste,
+        RecuperacionVencida,
+        RecuperacionEnCurso,
+        HandleInvalido,
+        HandleEnUso,
+        HandleReservado,
+        SinHandle,
+        YaEsFavorito,
+        NoEsFavorito,
+        YaSigue,
+        NoSigue,
+        YaReportado,
+        ReporteNoExiste,
+        VendedorSuspendido,
+        PromocionActiva,
+        FondosInsuficientes,
+        UmbralNoAlcanzado,
+        PlazoNoCumplido,
+        PublicacionOculta,
+        PromocionesDeshabilitadas,
+        RolNoAsignado,
     }
    
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
@@ -42,7 +210,12 @@ mod usuarios_sistema {
         apellido:String,
         email:String,
         id:AccountId,
-        rol: Rol,
+        roles: Roles,
+        verificacion: Option<Verificacion>,
+        deposito: Balance, //Lo que pagó al registrarse. Se devuelve al darse de baja.
+        fecha_registro: FechaBloque,
+        ultima_actividad: Timestamp, //Se actualiza en cada mensaje que modifica el estado.
+        handle: Option<String>, //Nombre de usuario único y público.
         //productos: Option<Producto>, //Si es vendedor tiene que tener una lista de sus productos.
         //orden_compra: Option<OrdenDeCompra>, //Si es comprador tiene que tener una orden de compra.
         //Duda: Tendría que tener un historial de sus propias transacciones?
@@ -56,149 +229,2339 @@ mod usuarios_sistema {
     )]
     #[derive(Clone, PartialEq, Eq, Debug)]
 
-    pub enum Rol {
-        Comprador,
-        Vendedor,
-        Ambos,
-    }
+    pub enum Rol {
+        Comprador,
+        Vendedor,
+        Ambos, //Se mantiene por compatibilidad: equivale a Comprador y Vendedor juntos.
+        Moderador,
+        Arbitro,
+        Repartidor,
+        Admin,
+    }
+
+    /// Momento de la cadena en el que ocurrió algo.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+
+    pub struct FechaBloque {
+        timestamp: Timestamp,
+        bloque: BlockNumber,
+    }
+
+    /// Conjunto de roles de un usuario, guardado como bits (un bit por rol).
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+
+    pub struct Roles(u8);
+
+    /// Acciones que puede habilitar un rol según la tabla de permisos.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+
+    pub enum Permiso {
+        Comprar,
+        Publicar,
+        Moderar,
+        Arbitrar,
+        Entregar,
+        Administrar,
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct Categoria {
+        id: u32,
+        nombre: String,
+        padre: Option<u32>, //None si es una categoría raíz.
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct Publicacion {
+        id: u32,
+        vendedor: AccountId,
+        nombre: String,
+        descripcion: String,
+        precio: Balance,
+        stock: u32,
+        categoria: u32,
+        oculta: bool, //La ocultó un moderador o juntó demasiados reportes.
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+
+    pub enum ModoRegistro {
+        Abierto, //Cualquiera puede registrarse.
+        SoloInvitacion, //Hace falta un código de invitación emitido por un usuario o por el admin.
+        ConAprobacion, //Los registros quedan pendientes hasta que el admin los aprueba.
+    }
+
+    /// Listas de cuentas o de ids que se pueden recorrer. Se guardan repartidas en Mappings para que
+    /// no crezca el storage raíz, que ink! carga y guarda entero en cada mensaje.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+
+    pub enum Lista {
+        RegistrosPendientes,
+        SolicitudesVendedor, //Solicitudes de vendedor que esperan revisión.
+        CuentasRegistradas, //Para poder recorrer los usuarios (el Mapping no se puede recorrer).
+        Seguidos(AccountId), //Vendedores que sigue un usuario.
+        Seguidores(AccountId), //Usuarios que siguen a un vendedor.
+        ReportesAbiertos, //Ids de reportes que todavía no cerró ningún moderador.
+        ReportesSobre(AccountId), //Ids de reportes abiertos contra un usuario.
+        Promocionadas, //Publicaciones con promoción, vencida o no.
+        Protegidos(AccountId), //Cuentas que tienen a un usuario como guardián.
+        ReportesDe(AccountId), //Ids de los reportes que hizo un usuario.
+        InvitacionesDe(AccountId), //Números de las invitaciones sin usar que emitió un usuario.
+        ProductosDe(u32), //Índice: id de categoría -> ids de productos.
+        PublicacionesDe(AccountId), //Índice: vendedor -> ids de sus publicaciones.
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub enum EstadoSolicitud {
+        Pendiente,
+        Aprobada,
+        Rechazada(String), //Motivo del rechazo.
+    }
+
+    /// Solicitud para operar como vendedor. Se crea cuando un usuario obtiene el rol de vendedor.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct SolicitudVendedor {
+        estado: EstadoSolicitud,
+        revisor: Option<AccountId>,
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub enum TipoCambio {
+        Registro(Roles),
+        RolAgregado(Rol),
+        RolQuitado(Rol),
+        SolicitudVendedorResuelta(EstadoSolicitud),
+        PerfilVendedorEditado,
+        Verificado(u8), //Nivel de verificación asignado.
+        CuentaMigrada(AccountId), //Cuenta de la que se migró.
+        HandleCambiado(Option<String>), //Handle nuevo, o None si se liberó.
+        Suspendido,
+        Reactivado,
+    }
+
+    /// Entrada del historial de un usuario: qué cambió, quién lo cambió y en qué bloque.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct Cambio {
+        tipo: TipoCambio,
+        autor: AccountId,
+        bloque: BlockNumber,
+    }
+
+    /// Cuentas de confianza que pueden recuperar el usuario si pierde su clave.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct Guardianes {
+        cuentas: Vec<AccountId>,
+        umbral: u8, //Cantidad de aprobaciones necesarias.
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct Recuperacion {
+        nueva: AccountId,
+        aprobaciones: Vec<AccountId>,
+        inicio: Timestamp,
+        umbral_alcanzado: Option<Timestamp>, //Desde acá corre el plazo de cancelación.
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+
+    pub enum MotivoReporte {
+        Estafa,
+        ProductoProhibido,
+        Spam,
+        ContenidoOfensivo,
+        Otro,
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub enum ObjetoReporte {
+        Publicacion(u32),
+        Usuario(AccountId),
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct Reporte {
+        id: u32,
+        objeto: ObjetoReporte,
+        autor: AccountId,
+        motivo: MotivoReporte,
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct Promocion {
+        oferta: Balance, //Lo pagado por unidad de tiempo; ordena los destacados.
+        vencimiento: Timestamp,
+    }
+
+    /// Verificación de identidad (KYC) que un verificador hizo sobre un usuario.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct Verificacion {
+        nivel: u8,
+        vencimiento: Timestamp,
+        atestacion: Hash, //Hash del documento que respalda la verificación (se guarda fuera de la cadena).
+        verificador: AccountId,
+    }
+
+    /// Si el valor total publicado por un vendedor (la suma de precio * stock de sus publicaciones)
+    /// supera `valor_maximo`, el vendedor tiene que estar verificado con al menos `nivel_minimo`.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct ReglaVerificacion {
+        valor_maximo: Balance,
+        nivel_minimo: u8,
+    }
+
+    /// Datos públicos de la tienda de un vendedor.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+
+    pub struct PerfilVendedor {
+        nombre_tienda: String,
+        descripcion: String,
+        logo: Option<Hash>, //Hash del contenido del logo (se guarda fuera de la cadena).
+        contacto: String,
+        id_fiscal: String,
+    }
+
+    /// Contenido de una dirección de envío. El comprador puede guardarla en claro,
+    /// cifrada (solo la puede leer quien tenga la clave) o solo su hash.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub enum ContenidoDireccion {
+        Texto(String),
+        Cifrada(Vec<u8>),
+        Hash(Hash),
+    }
+
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct Direccion {
+        id: u32,
+        contenido: ContenidoDireccion,
+    }
+
+    /// Datos que firma un usuario para que un tercero (relayer) lo registre.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub struct SolicitudRegistro {
+        nombre: String,
+        apellido: String,
+        email: String,
+        rol: Rol,
+        nonce: u64,
+        vencimiento: Timestamp,
+        invitacion: Option<String>, //Código de invitación, para el modo SoloInvitacion.
+    }
+
+    /// Firma con la que un usuario autoriza a un tercero (relayer) a registrarlo.
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[derive(Clone, PartialEq, Eq, Debug)]
+
+    pub enum FirmaRegistro {
+        /// Para sr25519 la clave pública es el propio AccountId del firmante.
+        Sr25519 { firmante: AccountId, firma: [u8; 64] },
+        /// Para ECDSA se recupera la clave a partir de la firma y tiene que corresponder a `firmante`:
+        /// si no, un mensaje adulterado recuperaría otra clave y registraría otra cuenta.
+        Ecdsa { firmante: AccountId, firma: [u8; 65] },
+    }
+
+    impl Sistema {
+        /// Constructor that initializes the `bool` value to the given `init_value`.
+        #[ink(constructor)]
+        pub fn new(init_value: bool) -> Self {
+            let mut sistema = Self {
+                value: init_value,
+                usuarios: Mapping::new(),
+                admin: Self::env().caller(),
+                categorias: Mapping::new(),
+                cantidad_categorias: 0,
+                publicaciones: Mapping::new(),
+                cantidad_publicaciones: 0,
+                perfiles_vendedor: Mapping::new(),
+                direcciones: Mapping::new(),
+                direccion_predeterminada: Mapping::new(),
+                nonces: Mapping::new(),
+                verificadores: Mapping::new(),
+                umbral_verificacion: None,
+                valor_publicado: Mapping::new(),
+                tarifa_registro: 0,
+                modo_registro: ModoRegistro::Abierto,
+                invitaciones: Mapping::new(),
+                codigos_invitacion: Mapping::new(),
+                cantidad_invitaciones: 0,
+                registros_pendientes: Mapping::new(),
+                permisos: Mapping::new(),
+                solicitudes_vendedor: Mapping::new(),
+                historial: Mapping::new(),
+                cantidad_cambios: Mapping::new(),
+                periodo_inactividad: None,
+                recompensa_limpieza: 0,
+                saldos: Mapping::new(),
+                migraciones_pendientes: Mapping::new(),
+                guardianes: Mapping::new(),
+                recuperaciones: Mapping::new(),
+                ventana_recuperacion: 7 * 24 * 60 * 60 * 1000, //Una semana.
+                plazo_cancelacion: 2 * 24 * 60 * 60 * 1000, //Dos días.
+                handles: Mapping::new(),
+                handles_reservados: Mapping::new(),
+                favoritos: Mapping::new(),
+                guardados: Mapping::new(),
+                contador_favoritos: Mapping::new(),
+                reportes: Mapping::new(),
+                cantidad_reportes: 0,
+                reportantes: Mapping::new(),
+                contador_reportes: Mapping::new(),
+                umbral_reportes: 5,
+                advertencias: Mapping::new(),
+                suspendidos: Mapping::new(),
+                tesoreria: 0,
+                precio_promocion: 0,
+                unidad_promocion: 60 * 60 * 1000, //Una hora.
+                promociones: Mapping::new(),
+                elementos_lista: Mapping::new(),
+                posiciones_lista: Mapping::new(),
+                elementos_lista_ids: Mapping::new(),
+                posiciones_lista_ids: Mapping::new(),
+                largo_lista: Mapping::new(),
+            };
+            sistema.permisos.insert(Rol::Comprador, &Vec::from([Permiso::Comprar]));
+            sistema.permisos.insert(Rol::Vendedor, &Vec::from([Permiso::Publicar]));
+            sistema.permisos.insert(Rol::Moderador, &Vec::from([Permiso::Moderar]));
+            sistema.permisos.insert(Rol::Arbitro, &Vec::from([Permiso::Arbitrar]));
+            sistema.permisos.insert(Rol::Repartidor, &Vec::from([Permiso::Entregar]));
+            sistema.permisos.insert(Rol::Admin, &Vec::from([Permiso::Administrar, Permiso::Moderar]));
+            sistema
+        }
+
+        /// Constructor that initializes the `bool` value to `false`.
+        ///
+        /// Constructors can delegate to other constructors.
+        #[ink(constructor)]
+        pub fn default() -> Self {
+            Self::new(Default::default())
+        }
+
+        /// A message that can be called on instantiated contracts.
+        /// This one flips the value of the stored `bool` from `true`
+        /// to `false` and vice versa.
+        #[ink(message)]
+        pub fn flip(&mut self) {
+            self.value = !self.value;
+        }
+
+        /// Simply returns the current value of our `bool`.
+        #[ink(message)]
+        pub fn get(&self) -> bool {
+            self.value
+        }
+
+        //Verificadores del sistema.
+        fn _existe_usuario(&self, id: AccountId) -> Result<bool, ErrorSistema> {
+            if self.usuarios.get(id).is_some() {
+                Ok(true)
+            } else {
+                Err(ErrorSistema::UsuarioNoExiste)
+            }
+        }
+
+        #[ink(message)]
+        pub fn es_vendedor(&self) -> Result<bool, ErrorSistema> { //Duda: Está bien recibirlo como parámetro al id o lo tengo que obtener del caller?
+            //Duda: Debería preguntar acá o en el privado si el usuario existe?
+
+            let id = self.env().caller(); //Está bien esto? 
+            self._es_vendedor(id)
+        }
+
+        fn _es_vendedor(&self, id: AccountId) -> Result<bool, ErrorSistema> {
+            //Si existe el usuario
+                //lo encuentro
+                //y verifico si es vendedor o ambos.
+            //Si no existo -> ErrorSistema::UsuarioNoExiste
+
+            if (self._existe_usuario(id)).is_err() {
+                Err(ErrorSistema::UsuarioNoExiste)
+            } else {
+                //Vendedor es quien tiene el permiso de publicar (que además exige la solicitud aprobada).
+                Ok(self._tiene_permiso(id, Permiso::Publicar))
+            }
+        }
+
+        //Mismas dudas que en es_vendedor.
+        #[ink(message)]
+        pub fn es_comprador(&self) -> Result<bool, ErrorSistema> { 
+            let id = self.env().caller(); 
+            self._es_comprador(id)
+        }
+
+        fn _es_comprador(&self, id: AccountId) -> Result<bool, ErrorSistema> {
+            //Si existe el usuario
+                //lo encuentro
+                //y verifico si es comprador o ambos.
+            //Si no existo -> ErrorSistema::UsuarioNoExiste
+
+            if (self._existe_usuario(id)).is_err() {
+                Err(ErrorSistema::UsuarioNoExiste)
+            } else {
+                //Comprador es quien tiene el permiso de comprar según la tabla de permisos.
+                Ok(self._tiene_permiso(id, Permiso::Comprar))
+            }
+        }
+
+        //Funciones asociadas a usuarios. 
+
+        #[ink(message, payable)]
+        pub fn registrar_usuario(&mut self, nombre:String, apellido:String, email:String, rol:Rol) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller(); // Se obtiene el AccountId del usuario que llama a la función.
+
+            let deposito = self._cobrar_tarifa_registro()?;
+            self._admitir_registro(Usuario::nuevo(nombre, apellido, email, id, Roles::from(rol), deposito), None)
+        }
+
+        #[ink(message, payable)]
+        pub fn registrar_usuario_con_invitacion(&mut self, nombre:String, apellido:String, email:String, rol:Rol, codigo: String) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+
+            let deposito = self._cobrar_tarifa_registro()?;
+            self._admitir_registro(Usuario::nuevo(nombre, apellido, email, id, Roles::from(rol), deposito), Some(codigo))
+        }
+
+        //Aplica el modo de registro configurado: registra al usuario, consume su invitación o lo deja pendiente.
+        fn _admitir_registro(&mut self, user: Usuario, codigo: Option<String>) -> Result<(), ErrorSistema> {
+            //Al registrarse solo se pueden pedir roles de comprador y/o vendedor. El resto los asigna el admin.
+            if !user.roles.son_autoasignables() {
+                return Err(ErrorSistema::RolNoPermitido);
+            }
+            match self.modo_registro {
+                ModoRegistro::Abierto => {}
+                ModoRegistro::SoloInvitacion => {
+                    let hash = Self::_hash_codigo(&codigo.ok_or(ErrorSistema::InvitacionRequerida)?, &user.id);
+                    let (emisor, numero) = self.invitaciones.take(hash).ok_or(ErrorSistema::InvitacionInvalida)?;
+                    self.codigos_invitacion.remove(numero);
+                    self._quitar_id_de_lista(Lista::InvitacionesDe(emisor), numero);
+                }
+                ModoRegistro::ConAprobacion => {
+                    if self.usuarios.contains(user.id) {
+                        return Err(ErrorSistema::UsuarioYaRegistrado);
+                    }
+                    if self.registros_pendientes.contains(user.id) {
+                        return Err(ErrorSistema::RegistroPendiente);
+                    }
+                    self._agregar_a_lista(Lista::RegistrosPendientes, user.id)?;
+                    self.registros_pendientes.insert(user.id, &user);
+                    return Ok(());
+                }
+            }
+            let Usuario { nombre, apellido, email, id, roles, deposito, .. } = user;
+            self._registrar_usuario(nombre, apellido, email, roles, id, deposito)
+        }
+
+        #[ink(message)]
+        pub fn configurar_modo_registro(&mut self, modo: ModoRegistro) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            self.modo_registro = modo;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_modo_registro(&self) -> ModoRegistro {
+            self.modo_registro
+        }
+
+        /// Emite una invitación de un solo uso para una cuenta en particular. Se guarda solo el hash del código junto
+        /// con la cuenta invitada: si alguien copia el código de una transacción no le sirve para registrar otra cuenta.
+        /// El hash se calcula fuera de la cadena, así el código no pasa por ningún nodo antes de usarse:
+        /// blake2_256 de los bytes del código seguidos de los 32 bytes de la cuenta invitada.
+        /// Pueden emitirla los usuarios registrados y el admin.
+        #[ink(message)]
+        pub fn crear_invitacion(&mut self, hash_codigo: Hash) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            if self._solo_admin().is_err() {
+                self._existe_usuario(id)?;
+            }
+            if self.invitaciones.contains(hash_codigo) {
+                return Err(ErrorSistema::InvitacionInvalida);
+            }
+            let numero = self.cantidad_invitaciones;
+            self.cantidad_invitaciones = numero.checked_add(1).ok_or(ErrorSistema::Desbordamiento)?;
+            self.invitaciones.insert(hash_codigo, &(id, numero));
+            self.codigos_invitacion.insert(numero, &hash_codigo);
+            self._agregar_id_a_lista(Lista::InvitacionesDe(id), numero)
+        }
+
+        //No es un mensaje: consultarlo le mandaría el código a un nodo RPC. Se usa en los tests.
+        #[cfg(test)]
+        fn hash_codigo_invitacion(&self, codigo: String, invitado: AccountId) -> Hash {
+            Self::_hash_codigo(&codigo, &invitado)
+        }
+
+        fn _hash_codigo(codigo: &str, invitado: &AccountId) -> Hash {
+            let mut datos = Vec::from(codigo.as_bytes());
+            datos.extend_from_slice(invitado.as_ref());
+            let mut hash = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(&datos, &mut hash);
+            Hash::from(hash)
+        }
+
+        /// Lista los registros pendientes de a páginas. La primera página es la 0.
+        #[ink(message)]
+        pub fn listar_registros_pendientes(&self, pagina: u32, por_pagina: u32) -> Vec<Usuario> {
+            self._pagina_de_lista(Lista::RegistrosPendientes, pagina, por_pagina)
+                .iter()
+                .filter_map(|id| self.registros_pendientes.get(id))
+                .collect()
+        }
+
+        #[ink(message)]
+        pub fn aprobar_registro(&mut self, cuenta: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            let Usuario { nombre, apellido, email, id, roles, deposito, .. } = self._sacar_de_pendientes(cuenta)?;
+            self._registrar_usuario(nombre, apellido, email, roles, id, deposito)
+        }
+
+        /// Rechaza un registro pendiente y le devuelve el depósito al solicitante.
+        #[ink(message)]
+        pub fn rechazar_registro(&mut self, cuenta: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            let user = self._sacar_de_pendientes(cuenta)?;
+            if user.deposito > 0 {
+                self.env().transfer(cuenta, user.deposito).map_err(|_| ErrorSistema::TransferenciaFallida)?;
+            }
+            Ok(())
+        }
+
+        fn _sacar_de_pendientes(&mut self, cuenta: AccountId) -> Result<Usuario, ErrorSistema> {
+            let user = self.registros_pendientes.take(cuenta).ok_or(ErrorSistema::SolicitudNoExiste)?;
+            self._quitar_de_lista(Lista::RegistrosPendientes, cuenta);
+            Ok(user)
+        }
+
+        //Devuelve lo transferido si alcanza para cubrir la tarifa de registro.
+        fn _cobrar_tarifa_registro(&self) -> Result<Balance, ErrorSistema> {
+            let pagado = self.env().transferred_value();
+            if pagado < self.tarifa_registro {
+                return Err(ErrorSistema::PagoInsuficiente);
+            }
+            Ok(pagado)
+        }
+
+        #[ink(message)]
+        pub fn configurar_tarifa_registro(&mut self, tarifa: Balance) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            self.tarifa_registro = tarifa;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_tarifa_registro(&self) -> Balance {
+            self.tarifa_registro
+        }
+
+        /// Elimina al caller del sistema y le devuelve el depósito que pagó al registrarse.
+        /// No puede darse de baja un vendedor que todavía tiene publicaciones.
+        #[ink(message)]
+        pub fn darse_de_baja(&mut self) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self._dar_de_baja(id)?;
+            Ok(())
+        }
+
+        fn _dar_de_baja(&mut self, id: AccountId) -> Result<Balance, ErrorSistema> {
+            let user = self._eliminar_usuario(id)?;
+            if user.deposito > 0 {
+                self.env().transfer(id, user.deposito).map_err(|_| ErrorSistema::TransferenciaFallida)?;
+            }
+            Ok(user.deposito)
+        }
+
+        //Borra al usuario y todo lo asociado a él (menos su historial). Devuelve el usuario borrado.
+        fn _eliminar_usuario(&mut self, id: AccountId) -> Result<Usuario, ErrorSistema> {
+            let user = self._get_user(id)?;
+            if self.largo_lista.get(Lista::PublicacionesDe(id)).unwrap_or(0) > 0 {
+                return Err(ErrorSistema::TienePublicaciones);
+            }
+
+            self.usuarios.remove(id);
+            if let Some(handle) = &user.handle {
+                self.handles.remove(handle);
+            }
+            self._quitar_de_lista(Lista::CuentasRegistradas, id);
+            self.perfiles_vendedor.remove(id);
+            self.valor_publicado.remove(id);
+            self.solicitudes_vendedor.remove(id);
+            self._quitar_de_lista(Lista::SolicitudesVendedor, id);
+            self.direcciones.remove(id);
+            self.direccion_predeterminada.remove(id);
+            for guardian in self.guardianes.take(id).map(|guardianes| guardianes.cuentas).unwrap_or_default() {
+                self._quitar_de_lista(Lista::Protegidos(guardian), id);
+            }
+            self.recuperaciones.remove(id);
+            for producto in self.favoritos.take(id).unwrap_or_default() {
+                self.guardados.remove((producto, id));
+                if let Some(cantidad) = self.contador_favoritos.get(producto) {
+                    self.contador_favoritos.insert(producto, &cantidad.saturating_sub(1));
+                }
+            }
+            for vendedor in self._vaciar_lista(Lista::Seguidos(id)) {
+                self._quitar_de_lista(Lista::Seguidores(vendedor), id);
+            }
+            for seguidor in self._vaciar_lista(Lista::Seguidores(id)) {
+                self._quitar_de_lista(Lista::Seguidos(seguidor), id);
+            }
+            self.advertencias.remove(id);
+            self.suspendidos.remove(id);
+            self.contador_reportes.remove(ObjetoReporte::Usuario(id));
+            //Los reportes contra la cuenta siguen en la cola, pero una cuenta nueva con el mismo id no los hereda.
+            for reporte in self._vaciar_lista_ids(Lista::ReportesSobre(id)) {
+                if let Some(Reporte { autor, .. }) = self.reportes.get(reporte) {
+                    self.reportantes.remove((ObjetoReporte::Usuario(id), autor));
+                }
+            }
+            self._vaciar_lista_ids(Lista::ReportesDe(id));
+            self._vaciar_lista_ids(Lista::InvitacionesDe(id));
+            Ok(user)
+        }
+
+        /// Cualquiera puede eliminar cuentas que lleven más del período configurado sin actividad y no tengan publicaciones.
+        /// Las cuentas que no cumplen se saltean. A quien llama se le paga una recompensa por cuenta, sacada del depósito
+        /// de esa cuenta; el resto del depósito se le devuelve al dueño. Si algún pago falla (por ejemplo, la cuenta ya
+        /// no existe en la cadena) queda como saldo a retirar y la limpieza sigue. Devuelve cuántas cuentas se eliminaron.
+        #[ink(message)]
+        pub fn limpiar_inactivos(&mut self, cuentas: Vec<AccountId>) -> Result<u32, ErrorSistema> {
+            self._marcar_actividad();
+            let limpiador = self.env().caller();
+            let periodo = match self.periodo_inactividad {
+                Some(periodo) => periodo,
+                None => return Ok(0),
+            };
+
+            let mut eliminadas: u32 = 0;
+            for cuenta in cuentas {
+                if !self._inactivo(cuenta, periodo) || self.largo_lista.get(Lista::PublicacionesDe(cuenta)).unwrap_or(0) > 0 {
+                    continue;
+                }
+                let user = self._eliminar_usuario(cuenta)?;
+                let recompensa = self.recompensa_limpieza.min(user.deposito);
+                let devolucion = user.deposito.saturating_sub(recompensa);
+                self._pagar_o_acreditar(cuenta, devolucion)?;
+                self._pagar_o_acreditar(limpiador, recompensa)?;
+                self.env().emit_event(UsuarioEliminado { cuenta, limpiador, recompensa });
+                eliminadas = eliminadas.saturating_add(1);
+            }
+            Ok(eliminadas)
+        }
+
+        //Un pago que falla no tiene que frenar al resto: se guarda como saldo del destinatario.
+        fn _pagar_o_acreditar(&mut self, destino: AccountId, monto: Balance) -> Result<(), ErrorSistema> {
+            if monto == 0 || self.env().transfer(destino, monto).is_ok() {
+                return Ok(());
+            }
+            let saldo = self.saldos.get(destino).unwrap_or(0);
+            self.saldos.insert(destino, &saldo.checked_add(monto).ok_or(ErrorSistema::Desbordamiento)?);
+            Ok(())
+        }
+
+        /// Transfiere al caller los pagos que quedaron a su nombre porque no se le pudieron hacer en su momento.
+        #[ink(message)]
+        pub fn retirar_saldo(&mut self) -> Result<Balance, ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            let saldo = self.saldos.take(id).ok_or(ErrorSistema::FondosInsuficientes)?;
+            self.env().transfer(id, saldo).map_err(|_| ErrorSistema::TransferenciaFallida)?;
+            Ok(saldo)
+        }
+
+        #[ink(message)]
+        pub fn get_saldo(&self, cuenta: AccountId) -> Balance {
+            self.saldos.get(cuenta).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn configurar_limpieza(&mut self, periodo_inactividad: Option<Timestamp>, recompensa: Balance) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            self.periodo_inactividad = periodo_inactividad;
+            self.recompensa_limpieza = recompensa;
+            Ok(())
+        }
+
+
+        //Siempre lo marca como ya registrado (por más de que no lo esté) ????
+        fn _registrar_usuario(&mut self, nombre:String, apellido:String, email:String, roles:Roles, id:AccountId, deposito: Balance) -> Result<(), ErrorSistema>{
+            // Chequear que el usuario a registrar no exista en el sistema. (Solo registrar usuarios nuevos)
+            if self.usuarios.get(id).is_some() { //Busca match en el mapping.
+                return Err(ErrorSistema::UsuarioYaRegistrado);
+            }                
+            
+            let mut user = Usuario::nuevo(nombre, apellido, email, id, roles, deposito);
+            user.fecha_registro = FechaBloque { timestamp: self.env().block_timestamp(), bloque: self.env().block_number() };
+            user.ultima_actividad = user.fecha_registro.timestamp;
+            self.usuarios.insert(id, &user);
+            self._agregar_a_lista(Lista::CuentasRegistradas, id)?;
+            self._registrar_cambio(id, TipoCambio::Registro(roles))?;
+            self._crear_solicitud_vendedor_si_corresponde(id)?;
+            Ok(())
+        }
+
+        /// Registra al firmante de `firma` pagando el caller (relayer) la transacción.
+        /// La firma tiene que ser sobre `mensaje_registro` con el nonce actual del firmante y no estar vencida.
+        /// Si hay tarifa de registro, la paga el relayer y el depósito queda a nombre del firmante.
+        #[ink(message, payable)]
+        pub fn registrar_usuario_firmado(&mut self, solicitud: SolicitudRegistro, firma: FirmaRegistro) -> Result<AccountId, ErrorSistema> {
+            self._marcar_actividad();
+            if self.env().block_timestamp() > solicitud.vencimiento {
+                return Err(ErrorSistema::FirmaVencida);
+            }
+            let mensaje = self.mensaje_registro(solicitud.clone());
+            let id = Self::_verificar_firma(&mensaje, &firma)?;
+
+            if self.nonces.get(id).unwrap_or(0) != solicitud.nonce {
+                return Err(ErrorSistema::NonceInvalido);
+            }
+            let deposito = self._cobrar_tarifa_registro()?;
+            let SolicitudRegistro { nombre, apellido, email, rol, nonce, invitacion, .. } = solicitud;
+            self._admitir_registro(Usuario::nuevo(nombre, apellido, email, id, Roles::from(rol), deposito), invitacion)?;
+            self.nonces.insert(id, &nonce.checked_add(1).ok_or(ErrorSistema::Desbordamiento)?);
+            Ok(id)
+        }
+
+        /// Bytes que el usuario tiene que firmar para `registrar_usuario_firmado`.
+        /// Incluye la dirección del contrato para que la firma no sirva en otro despliegue.
+        #[ink(message)]
+        pub fn mensaje_registro(&self, solicitud: SolicitudRegistro) -> Vec<u8> {
+            (self.env().account_id(), solicitud).encode()
+        }
+
+        #[ink(message)]
+        pub fn get_nonce(&self, cuenta: AccountId) -> u64 {
+            self.nonces.get(cuenta).unwrap_or(0)
+        }
+
+        //Devuelve la cuenta que firmó el mensaje.
+        fn _verificar_firma(mensaje: &[u8], firma: &FirmaRegistro) -> Result<AccountId, ErrorSistema> {
+            match firma {
+                FirmaRegistro::Sr25519 { firmante, firma } => {
+                    ink::env::sr25519_verify(firma, mensaje, firmante.as_ref()).map_err(|_| ErrorSistema::FirmaInvalida)?;
+                    Ok(*firmante)
+                }
+                FirmaRegistro::Ecdsa { firmante, firma } => {
+                    let mut hash_mensaje = <Blake2x256 as HashOutput>::Type::default();
+                    ink::env::hash_bytes::<Blake2x256>(mensaje, &mut hash_mensaje);
+                    let mut clave_publica = [0u8; 33];
+                    ink::env::ecdsa_recover(firma, &hash_mensaje, &mut clave_publica).map_err(|_| ErrorSistema::FirmaInvalida)?;
+                    //Igual que en Substrate, la cuenta de una clave ECDSA es el hash de su clave pública comprimida.
+                    let mut cuenta = <Blake2x256 as HashOutput>::Type::default();
+                    ink::env::hash_bytes::<Blake2x256>(&clave_publica, &mut cuenta);
+                    if AccountId::from(cuenta) != *firmante {
+                        return Err(ErrorSistema::FirmaInvalida);
+                    }
+                    Ok(*firmante)
+                }
+            }
+        }
+
+        #[ink(message)]
+        pub fn agregar_rol(&mut self, rol: Rol) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller(); // Se obtiene el AccountId del usuario que llama a la función.
+
+            self._agregar_rol(rol, id)
+        }
+
+        fn _agregar_rol(&mut self, rol: Rol, id: AccountId) -> Result<(), ErrorSistema> { //Hacer un agregar para cada rol distinto.
+            if !Roles::from(rol.clone()).son_autoasignables() {
+                return Err(ErrorSistema::RolNoPermitido);
+            }
+            // Verifica si el usuario existe.
+            if let Some(mut user) = self.usuarios.get(id) {  
+                user.agregar_rol(rol.clone())?; //Llama a la función del usuario que modifica su rol. (Lo delega)
+                self.usuarios.insert(id, &user); //Lo guardo modificado en le mapping.
+                self._registrar_cambio(id, TipoCambio::RolAgregado(rol))?;
+                self._crear_solicitud_vendedor_si_corresponde(id)?;
+                Ok(())
+            } else {
+                Err(ErrorSistema::UsuarioNoExiste)
+            }
+        }
+
+
+        //Funciones asociadas a roles y permisos.
+
+        #[ink(message)]
+        pub fn tiene_rol(&self, cuenta: AccountId, rol: Rol) -> Result<bool, ErrorSistema> {
+            Ok(self._get_user(cuenta)?.roles.contiene(&rol))
+        }
+
+        #[ink(message)]
+        pub fn tiene_permiso(&self, cuenta: AccountId, permiso: Permiso) -> Result<bool, ErrorSistema> {
+            self._existe_usuario(cuenta)?;
+            Ok(self._tiene_permiso(cuenta, permiso))
+        }
+
+        //Alcanza con que alguno de los roles del usuario habilite el permiso.
+        //Publicar además exige que un moderador haya aprobado la solicitud de vendedor.
+        fn _tiene_permiso(&self, cuenta: AccountId, permiso: Permiso) -> bool {
+            let Some(user) = self.usuarios.get(cuenta) else {
+                return false;
+            };
+            if permiso == Permiso::Publicar && !self._vendedor_aprobado(cuenta) {
+                return false;
+            }
+            user.roles.listar().iter().any(|rol| self.get_permisos(rol.clone()).contains(&permiso))
+        }
+
+        #[ink(message)]
+        pub fn get_roles(&self, cuenta: AccountId) -> Result<Vec<Rol>, ErrorSistema> {
+            Ok(self._get_user(cuenta)?.roles.listar())
+        }
+
+        #[ink(message)]
+        pub fn get_permisos(&self, rol: Rol) -> Vec<Permiso> {
+            self.permisos.get(rol).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn configurar_permisos(&mut self, rol: Rol, permisos: Vec<Permiso>) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            if rol == Rol::Ambos {
+                return Err(ErrorSistema::RolNoPermitido);
+            }
+            self.permisos.insert(rol, &permisos);
+            Ok(())
+        }
+
+        /// Permite al admin asignar cualquier rol, incluidos los que un usuario no puede pedir por sí mismo.
+        #[ink(message)]
+        pub fn asignar_rol(&mut self, cuenta: AccountId, rol: Rol) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            let mut user = self._get_user(cuenta)?;
+            user.agregar_rol(rol.clone())?;
+            self.usuarios.insert(cuenta, &user);
+            self._registrar_cambio(cuenta, TipoCambio::RolAgregado(rol))?;
+            self._crear_solicitud_vendedor_si_corresponde(cuenta)
+        }
+
+        #[ink(message)]
+        pub fn quitar_rol(&mut self, cuenta: AccountId, rol: Rol) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            let mut user = self._get_user(cuenta)?;
+            if !user.roles.contiene(&rol) {
+                return Err(ErrorSistema::RolNoAsignado);
+            }
+            user.roles.quitar(&rol);
+            self.usuarios.insert(cuenta, &user);
+            //Sin el rol de vendedor se descarta su solicitud: si lo vuelve a pedir, la tienen que aprobar de nuevo.
+            if !user.roles.contiene(&Rol::Vendedor) {
+                self.solicitudes_vendedor.remove(cuenta);
+                self._quitar_de_lista(Lista::SolicitudesVendedor, cuenta);
+            }
+            self._registrar_cambio(cuenta, TipoCambio::RolQuitado(rol))
+        }
+
+        //Funciones asociadas a la actividad de los usuarios.
+
+        //Si el caller es un usuario registrado, actualiza su última actividad.
+        fn _marcar_actividad(&mut self) {
+            let id = self.env().caller();
+            if let Some(mut user) = self.usuarios.get(id) {
+                user.ultima_actividad = self.env().block_timestamp();
+                self.usuarios.insert(id, &user);
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_actividad(&self, cuenta: AccountId) -> Result<(FechaBloque, Timestamp), ErrorSistema> {
+            let user = self._get_user(cuenta)?;
+            Ok((user.fecha_registro, user.ultima_actividad))
+        }
+
+        /// Recorre de a páginas las cuentas registradas y devuelve las que no tuvieron actividad en los últimos
+        /// `inactividad` milisegundos. Una página puede traer menos de `por_pagina` cuentas. Solo para el admin.
+        #[ink(message)]
+        pub fn listar_inactivos(&self, inactividad: Timestamp, pagina: u32, por_pagina: u32) -> Result<Vec<AccountId>, ErrorSistema> {
+            self._solo_admin()?;
+            Ok(self._pagina_de_lista(Lista::CuentasRegistradas, pagina, por_pagina)
+                .into_iter()
+                .filter(|cuenta| self._inactivo(*cuenta, inactividad))
+                .collect())
+        }
+
+        fn _inactivo(&self, cuenta: AccountId, inactividad: Timestamp) -> bool {
+            match self.usuarios.get(cuenta) {
+                Some(user) => self.env().block_timestamp().saturating_sub(user.ultima_actividad) > inactividad,
+                None => false,
+            }
+        }
+
+        //Funciones asociadas a handles.
+
+        const LARGO_MINIMO_HANDLE: usize = 3;
+        const LARGO_MAXIMO_HANDLE: usize = 20;
+
+        //Un handle válido tiene entre 3 y 20 caracteres: letras minúsculas, dígitos o '_'.
+        fn _validar_handle(handle: &str) -> Result<(), ErrorSistema> {
+            let largo_valido = (Self::LARGO_MINIMO_HANDLE..=Self::LARGO_MAXIMO_HANDLE).contains(&handle.len());
+            let caracteres_validos = handle.bytes().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'_');
+            if !largo_valido || !caracteres_validos {
+                return Err(ErrorSistema::HandleInvalido);
+            }
+            Ok(())
+        }
+
+        /// El caller reclama `handle`. Si ya tenía otro, lo libera.
+        #[ink(message)]
+        pub fn reclamar_handle(&mut self, handle: String) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self._asignar_handle(id, handle)
+        }
+
+        fn _asignar_handle(&mut self, id: AccountId, handle: String) -> Result<(), ErrorSistema> {
+            let mut user = self._get_user(id)?;
+            Self::_validar_handle(&handle)?;
+            if self.handles_reservados.contains(&handle) {
+                return Err(ErrorSistema::HandleReservado);
+            }
+            if self.handles.contains(&handle) {
+                return Err(ErrorSistema::HandleEnUso);
+            }
+            if let Some(anterior) = &user.handle {
+                self.handles.remove(anterior);
+            }
+            self.handles.insert(&handle, &id);
+            user.handle = Some(handle.clone());
+            self.usuarios.insert(id, &user);
+            self._registrar_cambio(id, TipoCambio::HandleCambiado(Some(handle)))
+        }
+
+        #[ink(message)]
+        pub fn liberar_handle(&mut self) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self._quitar_handle(id)?;
+            Ok(())
+        }
+
+        fn _quitar_handle(&mut self, id: AccountId) -> Result<String, ErrorSistema> {
+            let mut user = self._get_user(id)?;
+            let handle = user.handle.take().ok_or(ErrorSistema::SinHandle)?;
+            self.handles.remove(&handle);
+            self.usuarios.insert(id, &user);
+            self._registrar_cambio(id, TipoCambio::HandleCambiado(None))?;
+            Ok(handle)
+        }
+
+        /// Pasa el handle del caller a `destino`, que tiene que estar registrado y no tener handle.
+        #[ink(message)]
+        pub fn transferir_handle(&mut self, destino: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            if self._get_user(destino)?.handle.is_some() {
+                return Err(ErrorSistema::HandleEnUso);
+            }
+            let handle = self._quitar_handle(id)?;
+            self._asignar_handle(destino, handle)
+        }
+
+        #[ink(message)]
+        pub fn resolver_handle(&self, handle: String) -> Option<AccountId> {
+            self.handles.get(&handle)
+        }
+
+        /// Reserva un nombre para que nadie lo pueda reclamar. No afecta a quien ya lo tenga.
+        #[ink(message)]
+        pub fn reservar_handle(&mut self, handle: String) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            self.handles_reservados.insert(&handle, &());
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn liberar_reserva_handle(&mut self, handle: String) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            self.handles_reservados.remove(&handle);
+            Ok(())
+        }
+
+        //Funciones asociadas a la migración de cuentas.
+
+        /// Primer paso para mover el usuario del caller a `nueva`. La migración se completa cuando `nueva` llama a `aceptar_migracion`.
+        #[ink(message)]
+        pub fn migrar_cuenta(&mut self, nueva: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let vieja = self.env().caller();
+            self._existe_usuario(vieja)?;
+            self._verificar_cuenta_libre(nueva)?;
+            //Si otra cuenta ya pidió migrarse a `nueva`, no se pisa su pedido.
+            if self.migraciones_pendientes.get(nueva).is_some_and(|pendiente| pendiente != vieja) {
+                return Err(ErrorSistema::CuentaEnUso);
+            }
+            self.migraciones_pendientes.insert(nueva, &vieja);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn cancelar_migracion(&mut self, nueva: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            if self.migraciones_pendientes.get(nueva) != Some(self.env().caller()) {
+                return Err(ErrorSistema::MigracionNoExiste);
+            }
+            self.migraciones_pendientes.remove(nueva);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn aceptar_migracion(&mut self) -> Result<(), ErrorSistema> {
+            let nueva = self.env().caller();
+            let vieja = self.migraciones_pendientes.take(nueva).ok_or(ErrorSistema::MigracionNoExiste)?;
+            self._mover_cuenta(vieja, nueva)?;
+            self._marcar_actividad();
+            Ok(())
+        }
+
+        //Funciones asociadas a la recuperación social.
+
+        const MAXIMO_GUARDIANES: usize = 10;
+
+        /// El caller elige sus guardianes (hasta 10) y cuántos de ellos tienen que aprobar una recuperación.
+        #[ink(message)]
+        pub fn configurar_guardianes(&mut self, cuentas: Vec<AccountId>, umbral: u8) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self._existe_usuario(id)?;
+            let mut sin_repetidos = cuentas.clone();
+            sin_repetidos.sort();
+            sin_repetidos.dedup();
+            if cuentas.len() > Self::MAXIMO_GUARDIANES {
+                return Err(ErrorSistema::DatosInvalidos);
+            }
+            if umbral == 0 || usize::from(umbral) > cuentas.len() || sin_repetidos.len() != cuentas.len() || cuentas.contains(&id) {
+                return Err(ErrorSistema::DatosInvalidos);
+            }
+            for guardian in self.guardianes.get(id).map(|anteriores| anteriores.cuentas).unwrap_or_default() {
+                self._quitar_de_lista(Lista::Protegidos(guardian), id);
+            }
+            for guardian in cuentas.iter() {
+                self._agregar_a_lista(Lista::Protegidos(*guardian), id)?;
+            }
+            self.guardianes.insert(id, &Guardianes { cuentas, umbral });
+            //Un pedido en curso se aprobó con los guardianes anteriores, así que se descarta.
+            self.recuperaciones.remove(id);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_guardianes(&self, cuenta: AccountId) -> Option<Guardianes> {
+            self.guardianes.get(cuenta)
+        }
+
+        #[ink(message)]
+        pub fn configurar_recuperacion(&mut self, ventana: Timestamp, plazo_cancelacion: Timestamp) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            self.ventana_recuperacion = ventana;
+            self.plazo_cancelacion = plazo_cancelacion;
+            Ok(())
+        }
+
+        /// Un guardián de `cuenta` pide pasar el usuario a `nueva`. Cuenta como su aprobación.
+        /// Si ya había un pedido vencido, se reemplaza.
+        #[ink(message)]
+        pub fn iniciar_recuperacion(&mut self, cuenta: AccountId, nueva: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let guardian = self.env().caller();
+            self._solo_guardian(cuenta, guardian)?;
+            self._verificar_cuenta_libre(nueva)?;
+            let ahora = self.env().block_timestamp();
+            if let Some(recuperacion) = self.recuperaciones.get(cuenta) {
+                if !self._recuperacion_vencida(&recuperacion) {
+                    return Err(ErrorSistema::RecuperacionEnCurso);
+                }
+            }
+            let recuperacion = Recuperacion { nueva, aprobaciones: Vec::new(), inicio: ahora, umbral_alcanzado: None };
+            self._aprobar_recuperacion(cuenta, recuperacion, guardian)
+        }
+
+        #[ink(message)]
+        pub fn aprobar_recuperacion(&mut self, cuenta: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let guardian = self.env().caller();
+            self._solo_guardian(cuenta, guardian)?;
+            let recuperacion = self.recuperaciones.get(cuenta).ok_or(ErrorSistema::RecuperacionNoExiste)?;
+            if self._recuperacion_vencida(&recuperacion) {
+                return Err(ErrorSistema::RecuperacionVencida);
+            }
+            self._aprobar_recuperacion(cuenta, recuperacion, guardian)
+        }
+
+        fn _aprobar_recuperacion(&mut self, cuenta: AccountId, mut recuperacion: Recuperacion, guardian: AccountId) -> Result<(), ErrorSistema> {
+            if !recuperacion.aprobaciones.contains(&guardian) {
+                recuperacion.aprobaciones.push(guardian);
+            }
+            let umbral = self.guardianes.get(cuenta).ok_or(ErrorSistema::NoEsGuardian)?.umbral;
+            if recuperacion.umbral_alcanzado.is_none() && recuperacion.aprobaciones.len() >= usize::from(umbral) {
+                recuperacion.umbral_alcanzado = Some(self.env().block_timestamp());
+            }
+            self.recuperaciones.insert(cuenta, &recuperacion);
+            Ok(())
+        }
+
+        /// El dueño original puede cancelar una recuperación mientras no se haya ejecutado.
+        #[ink(message)]
+        pub fn cancelar_recuperacion(&mut self) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self.recuperaciones.take(id).ok_or(ErrorSistema::RecuperacionNoExiste)?;
+            Ok(())
+        }
+
+        /// Cualquiera puede ejecutar una recuperación aprobada una vez pasado el plazo de cancelación.
+        #[ink(message)]
+        pub fn ejecutar_recuperacion(&mut self, cuenta: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let recuperacion = self.recuperaciones.get(cuenta).ok_or(ErrorSistema::RecuperacionNoExiste)?;
+            let alcanzado = recuperacion.umbral_alcanzado.ok_or(ErrorSistema::UmbralNoAlcanzado)?;
+            if self.env().block_timestamp() < alcanzado.saturating_add(self.plazo_cancelacion) {
+                return Err(ErrorSistema::PlazoNoCumplido);
+            }
+            self._mover_cuenta(cuenta, recuperacion.nueva)
+        }
+
+        #[ink(message)]
+        pub fn get_recuperacion(&self, cuenta: AccountId) -> Option<Recuperacion> {
+            self.recuperaciones.get(cuenta)
+        }
+
+        fn _solo_guardian(&self, cuenta: AccountId, guardian: AccountId) -> Result<(), ErrorSistema> {
+            match self.guardianes.get(cuenta) {
+                Some(guardianes) if guardianes.cuentas.contains(&guardian) => Ok(()),
+                _ => Err(ErrorSistema::NoEsGuardian),
+            }
+        }
+
+        //Un pedido que no juntó las aprobaciones dentro de la ventana ya no sirve.
+        fn _recuperacion_vencida(&self, recuperacion: &Recuperacion) -> bool {
+            recuperacion.umbral_alcanzado.is_none()
+                && self.env().block_timestamp() > recuperacion.inicio.saturating_add(self.ventana_recuperacion)
+        }
+
+        fn _verificar_cuenta_libre(&self, cuenta: AccountId) -> Result<(), ErrorSistema> {
+            if self.usuarios.contains(cuenta) || self.registros_pendientes.contains(cuenta) {
+                return Err(ErrorSistema::CuentaEnUso);
+            }
+            Ok(())
+        }
+
+        //Cambia al guardián `vieja` por `nueva` en los guardianes de `protegida` y en su pedido de recuperación.
+        fn _reemplazar_guardian(&mut self, protegida: AccountId, vieja: AccountId, nueva: AccountId) -> Result<(), ErrorSistema> {
+            let Some(mut guardianes) = self.guardianes.get(protegida) else {
+                return Ok(());
+            };
+            if guardianes.cuentas.contains(&nueva) {
+                guardianes.cuentas.retain(|guardian| *guardian != vieja);
+                guardianes.umbral = guardianes.umbral.min(u8::try_from(guardianes.cuentas.len()).unwrap_or(u8::MAX));
+            } else {
+                for guardian in guardianes.cuentas.iter_mut().filter(|guardian| **guardian == vieja) {
+                    *guardian = nueva;
+                }
+            }
+            self.guardianes.insert(protegida, &guardianes);
+            self._agregar_a_lista(Lista::Protegidos(nueva), protegida)?;
+
+            if let Some(mut recuperacion) = self.recuperaciones.get(protegida) {
+                if recuperacion.aprobaciones.contains(&vieja) {
+                    recuperacion.aprobaciones.retain(|guardian| *guardian != vieja && *guardian != nueva);
+                    recuperacion.aprobaciones.push(nueva);
+                    self.recuperaciones.insert(protegida, &recuperacion);
+                }
+            }
+            Ok(())
+        }
+
+        //Mueve el usuario y todo lo que está indexado por su AccountId de `vieja` a `nueva`.
+        fn _mover_cuenta(&mut self, vieja: AccountId, nueva: AccountId) -> Result<(), ErrorSistema> {
+            self._verificar_cuenta_libre(nueva)?;
+            let mut user = self.usuarios.take(vieja).ok_or(ErrorSistema::UsuarioNoExiste)?;
+            user.id = nueva;
+            self.usuarios.insert(nueva, &user);
+            if let Some(handle) = &user.handle {
+                self.handles.insert(handle, &nueva);
+            }
+            self._reemplazar_en_lista(Lista::CuentasRegistradas, vieja, nueva);
+            if self.admin == vieja {
+                self.admin = nueva;
+            }
+            if self.verificadores.take(vieja).is_some() {
+                self.verificadores.insert(nueva, &());
+            }
+
+            if let Some(mut guardianes) = self.guardianes.take(vieja) {
+                for guardian in guardianes.cuentas.iter() {
+                    self._reemplazar_en_lista(Lista::Protegidos(*guardian), vieja, nueva);
+                }
+                //Nadie puede ser su propio guardián.
+                if self._quitar_de_lista(Lista::Protegidos(nueva), nueva) {
+                    guardianes.cuentas.retain(|guardian| *guardian != nueva);
+                    guardianes.umbral = guardianes.umbral.min(u8::try_from(guardianes.cuentas.len()).unwrap_or(u8::MAX));
+                }
+                self.guardianes.insert(nueva, &guardianes);
+            }
+            self.recuperaciones.remove(vieja);
+            //Donde la cuenta vieja era guardián de otros, pasa a serlo la nueva, también en los pedidos en curso.
+            for protegida in self._vaciar_lista(Lista::Protegidos(vieja)) {
+                self._reemplazar_guardian(protegida, vieja, nueva)?;
+            }
+            if let Some(perfil) = self.perfiles_vendedor.take(vieja) {
+                self.perfiles_vendedor.insert(nueva, &perfil);
+            }
+            if let Some(solicitud) = self.solicitudes_vendedor.take(vieja) {
+                self.solicitudes_vendedor.insert(nueva, &solicitud);
+            }
+            self._reemplazar_en_lista(Lista::SolicitudesVendedor, vieja, nueva);
+            if let Some(direcciones) = self.direcciones.take(vieja) {
+                self.direcciones.insert(nueva, &direcciones);
+            }
+            if let Some(direccion) = self.direccion_predeterminada.take(vieja) {
+                self.direccion_predeterminada.insert(nueva, &direccion);
+            }
+
+            if let Some(valor) = self.valor_publicado.take(vieja) {
+                self.valor_publicado.insert(nueva, &valor);
+            }
+            for id in self._mover_lista_ids(Lista::PublicacionesDe(vieja), Lista::PublicacionesDe(nueva))? {
+                if let Some(mut publicacion) = self.publicaciones.get(id) {
+                    publicacion.vendedor = nueva;
+                    self.publicaciones.insert(id, &publicacion);
+                }
+            }
+
+            if let Some(favoritos) = self.favoritos.take(vieja) {
+                for producto in favoritos.iter() {
+                    if self.guardados.take((*producto, vieja)).is_some() {
+                        self.guardados.insert((*producto, nueva), &());
+                    }
+                }
+                self.favoritos.insert(nueva, &favoritos);
+            }
+
+            for vendedor in self._mover_lista(Lista::Seguidos(vieja), Lista::Seguidos(nueva))? {
+                self._reemplazar_en_lista(Lista::Seguidores(vendedor), vieja, nueva);
+            }
+            for seguidor in self._mover_lista(Lista::Seguidores(vieja), Lista::Seguidores(nueva))? {
+                self._reemplazar_en_lista(Lista::Seguidos(seguidor), vieja, nueva);
+            }
+
+            if let Some(advertencias) = self.advertencias.take(vieja) {
+                self.advertencias.insert(nueva, &advertencias);
+            }
+            if self.suspendidos.take(vieja).is_some() {
+                self.suspendidos.insert(nueva, &());
+            }
+            if let Some(cantidad) = self.contador_reportes.take(ObjetoReporte::Usuario(vieja)) {
+                self.contador_reportes.insert(ObjetoReporte::Usuario(nueva), &cantidad);
+            }
+            for id in self._mover_lista_ids(Lista::ReportesSobre(vieja), Lista::ReportesSobre(nueva))? {
+                if let Some(mut reporte) = self.reportes.get(id) {
+                    if self.reportantes.take((ObjetoReporte::Usuario(vieja), reporte.autor)).is_some() {
+                        self.reportantes.insert((ObjetoReporte::Usuario(nueva), reporte.autor), &());
+                    }
+                    reporte.objeto = ObjetoReporte::Usuario(nueva);
+                    self.reportes.insert(id, &reporte);
+                }
+            }
+            //Los reportes que hizo siguen contando como suyos, así no puede volver a reportar lo mismo.
+            for id in self._mover_lista_ids(Lista::ReportesDe(vieja), Lista::ReportesDe(nueva))? {
+                if let Some(mut reporte) = self.reportes.get(id) {
+                    reporte.autor = nueva;
+                    if self.reportantes.take((reporte.objeto.clone(), vieja)).is_some() {
+                        self.reportantes.insert((reporte.objeto.clone(), nueva), &());
+                    }
+                    self.reportes.insert(id, &reporte);
+                }
+            }
+            for numero in self._mover_lista_ids(Lista::InvitacionesDe(vieja), Lista::InvitacionesDe(nueva))? {
+                if let Some(hash) = self.codigos_invitacion.get(numero) {
+                    self.invitaciones.insert(hash, &(nueva, numero));
+                }
+            }
+
+            //El historial se copia entero a la cuenta nueva. Si la cuenta nueva ya tenía historial
+            //(se registró y se dio de baja), se agrega a continuación para no pisar nada.
+            let anteriores = self.cantidad_cambios.get(nueva).unwrap_or(0);
+            let cantidad = self.cantidad_cambios.take(vieja).unwrap_or(0);
+            for indice in 0..cantidad {
+                if let Some(cambio) = self.historial.take((vieja, indice)) {
+                    self.historial.insert((nueva, anteriores.checked_add(indice).ok_or(ErrorSistema::Desbordamiento)?), &cambio);
+                }
+            }
+            self.cantidad_cambios.insert(nueva, &anteriores.checked_add(cantidad).ok_or(ErrorSistema::Desbordamiento)?);
+            self._registrar_cambio(nueva, TipoCambio::CuentaMigrada(vieja))
+        }
+
+        //Funciones asociadas al historial de cambios.
+
+        fn _registrar_cambio(&mut self, cuenta: AccountId, tipo: TipoCambio) -> Result<(), ErrorSistema> {
+            let indice = self.cantidad_cambios.get(cuenta).unwrap_or(0);
+            let cambio = Cambio { tipo, autor: self.env().caller(), bloque: self.env().block_number() };
+            self.historial.insert((cuenta, indice), &cambio);
+            self.cantidad_cambios.insert(cuenta, &indice.checked_add(1).ok_or(ErrorSistema::Desbordamiento)?);
+            Ok(())
+        }
+
+        /// Historial de cambios de roles y perfil de un usuario, del más viejo al más nuevo. La primera página es la 0.
+        #[ink(message)]
+        pub fn historial_usuario(&self, cuenta: AccountId, pagina: u32, por_pagina: u32) -> Vec<Cambio> {
+            let total = self.cantidad_cambios.get(cuenta).unwrap_or(0);
+            let inicio = pagina.saturating_mul(por_pagina).min(total);
+            let fin = inicio.saturating_add(por_pagina).min(total);
+            (inicio..fin).filter_map(|indice| self.historial.get((cuenta, indice))).collect()
+        }
+
+        fn _get_user(&self, id:AccountId)-> Result<Usuario, ErrorSistema>{
+
+            if let Some(user) = self.usuarios.get(id) {
+                Ok(user.clone())
+            } else {
+                Err(ErrorSistema::UsuarioNoExiste)
+            }
+        }
+
+        //Funciones asociadas a solicitudes de vendedor.
+
+        //Cuando un usuario obtiene el rol de vendedor queda pendiente de aprobación (si no tenía ya una solicitud).
+        fn _crear_solicitud_vendedor_si_corresponde(&mut self, id: AccountId) -> Result<(), ErrorSistema> {
+            let roles = self._get_user(id)?.roles;
+            if roles.contiene(&Rol::Vendedor) && !self.solicitudes_vendedor.contains(id) {
+                self.solicitudes_vendedor.insert(id, &SolicitudVendedor { estado: EstadoSolicitud::Pendiente, revisor: None });
+                self._agregar_a_lista(Lista::SolicitudesVendedor, id)?;
+            }
+            Ok(())
+        }
+
+        fn _vendedor_aprobado(&self, id: AccountId) -> bool {
+            matches!(self.solicitudes_vendedor.get(id), Some(SolicitudVendedor { estado: EstadoSolicitud::Aprobada, .. }))
+        }
+
+        /// Un vendedor rechazado puede volver a pedir la aprobación.
+        #[ink(message)]
+        pub fn reenviar_solicitud_vendedor(&mut self) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            match self.solicitudes_vendedor.get(id) {
+                Some(SolicitudVendedor { estado: EstadoSolicitud::Rechazada(_), .. }) => {
+                    self.solicitudes_vendedor.remove(id);
+                    self._crear_solicitud_vendedor_si_corresponde(id)
+                }
+                _ => Err(ErrorSistema::SolicitudNoExiste),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_solicitud_vendedor(&self, cuenta: AccountId) -> Result<SolicitudVendedor, ErrorSistema> {
+            self.solicitudes_vendedor.get(cuenta).ok_or(ErrorSistema::SolicitudNoExiste)
+        }
+
+        /// Lista las cuentas con solicitud de vendedor pendiente, de a páginas. La primera página es la 0.
+        #[ink(message)]
+        pub fn listar_solicitudes_vendedor(&self, pagina: u32, por_pagina: u32) -> Vec<AccountId> {
+            self._pagina_de_lista(Lista::SolicitudesVendedor, pagina, por_pagina)
+        }
+
+        //El admin también puede moderar.
+        fn _solo_moderador(&self) -> Result<AccountId, ErrorSistema> {
+            let id = self.env().caller();
+            if id != self.admin && !self._tiene_permiso(id, Permiso::Moderar) {
+                return Err(ErrorSistema::NoEsModerador);
+            }
+            Ok(id)
+        }
+
+        /// Aprueba la solicitud de vendedor de `cuenta` y le crea un perfil vacío.
+        #[ink(message)]
+        pub fn aprobar_vendedor(&mut self, cuenta: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let revisor = self._solo_moderador()?;
+            self._resolver_solicitud_vendedor(cuenta, EstadoSolicitud::Aprobada, revisor)?;
+            if !self.perfiles_vendedor.contains(cuenta) {
+                self.perfiles_vendedor.insert(cuenta, &PerfilVendedor::default());
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn rechazar_vendedor(&mut self, cuenta: AccountId, motivo: String) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let revisor = self._solo_moderador()?;
+            self._resolver_solicitud_vendedor(cuenta, EstadoSolicitud::Rechazada(motivo), revisor)
+        }
+
+        fn _resolver_solicitud_vendedor(&mut self, cuenta: AccountId, estado: EstadoSolicitud, revisor: AccountId) -> Result<(), ErrorSistema> {
+            match self.solicitudes_vendedor.get(cuenta) {
+                Some(SolicitudVendedor { estado: EstadoSolicitud::Pendiente, .. }) => {
+                    self.solicitudes_vendedor.insert(cuenta, &SolicitudVendedor { estado: estado.clone(), revisor: Some(revisor) });
+                    self._quitar_de_lista(Lista::SolicitudesVendedor, cuenta);
+                    self._registrar_cambio(cuenta, TipoCambio::SolicitudVendedorResuelta(estado))
+                }
+                _ => Err(ErrorSistema::SolicitudNoExiste),
+            }
+        }
+
+        //Funciones asociadas a perfiles de vendedor.
+
+        #[ink(message)]
+        pub fn editar_perfil_vendedor(&mut self, nombre_tienda: String, descripcion: String, logo: Option<Hash>, contacto: String, id_fiscal: String) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self._editar_perfil_vendedor(PerfilVendedor { nombre_tienda, descripcion, logo, contacto, id_fiscal }, id)
+        }
+
+        fn _editar_perfil_vendedor(&mut self, perfil: PerfilVendedor, id: AccountId) -> Result<(), ErrorSistema> {
+            if !self._es_vendedor(id)? {
+                return Err(ErrorSistema::NoEsVendedor);
+            }
+            if !self.perfiles_vendedor.contains(id) {
+                return Err(ErrorSistema::PerfilNoExiste);
+            }
+            self.perfiles_vendedor.insert(id, &perfil);
+            self._registrar_cambio(id, TipoCambio::PerfilVendedorEditado)
+        }
+
+        /// Cualquiera puede consultar el perfil de un vendedor.
+        #[ink(message)]
+        pub fn get_perfil_vendedor(&self, vendedor: AccountId) -> Result<PerfilVendedor, ErrorSistema> {
+            self.perfiles_vendedor.get(vendedor).ok_or(ErrorSistema::PerfilNoExiste)
+        }
+
+        //Funciones asociadas a verificación de identidad.
+
+        #[ink(message)]
+        pub fn agregar_verificador(&mut self, cuenta: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            self.verificadores.insert(cuenta, &());
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn quitar_verificador(&mut self, cuenta: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            self.verificadores.remove(cuenta);
+            Ok(())
+        }
+
+        /// Configura a partir de qué valor total publicado se exige que el vendedor esté verificado.
+        #[ink(message)]
+        pub fn configurar_umbral_verificacion(&mut self, regla: Option<ReglaVerificacion>) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            self.umbral_verificacion = regla;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn verificar_usuario(&mut self, cuenta: AccountId, nivel: u8, vencimiento: Timestamp, atestacion: Hash) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let verificador = self.env().caller();
+            if !self.verificadores.contains(verificador) {
+                return Err(ErrorSistema::NoEsVerificador);
+            }
+            let mut user = self._get_user(cuenta)?;
+            user.verificacion = Some(Verificacion { nivel, vencimiento, atestacion, verificador });
+            self.usuarios.insert(cuenta, &user);
+            self._registrar_cambio(cuenta, TipoCambio::Verificado(nivel))
+        }
+
+        #[ink(message)]
+        pub fn get_verificacion(&self, cuenta: AccountId) -> Result<Option<Verificacion>, ErrorSistema> {
+            Ok(self._get_user(cuenta)?.verificacion)
+        }
+
+        /// Devuelve el nivel de verificación vigente del usuario (0 si no está verificado o si venció).
+        #[ink(message)]
+        pub fn nivel_verificacion(&self, cuenta: AccountId) -> Result<u8, ErrorSistema> {
+            self._nivel_verificacion(cuenta)
+        }
+
+        fn _nivel_verificacion(&self, cuenta: AccountId) -> Result<u8, ErrorSistema> {
+            match self._get_user(cuenta)?.verificacion {
+                Some(v) if v.vencimiento >= self.env().block_timestamp() => Ok(v.nivel),
+                _ => Ok(0),
+            }
+        }
+
+        //Además de quien desplegó el contrato, administra quien tenga el permiso Administrar.
+        fn _solo_admin(&self) -> Result<(), ErrorSistema> {
+            let id = self.env().caller();
+            if id != self.admin && !self._tiene_permiso(id, Permiso::Administrar) {
+                return Err(ErrorSistema::NoEsAdmin);
+            }
+            Ok(())
+        }
+
+        //Funciones asociadas a direcciones de envío.
+
+        /// Agrega una dirección a la libreta del comprador. La primera que se agrega queda como predeterminada.
+        #[ink(message)]
+        pub fn agregar_direccion(&mut self, contenido: ContenidoDireccion) -> Result<u32, ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self._agregar_direccion(contenido, id)
+        }
+
+        fn _agregar_direccion(&mut self, contenido: ContenidoDireccion, comprador: AccountId) -> Result<u32, ErrorSistema> {
+            self._existe_usuario(comprador)?;
+            if !self._tiene_permiso(comprador, Permiso::Comprar) {
+                return Err(ErrorSistema::NoEsComprador);
+            }
+            let mut direcciones = self.direcciones.get(comprador).unwrap_or_default();
+            let id = match direcciones.iter().map(|d| d.id).max() {
+                Some(ultimo) => ultimo.checked_add(1).ok_or(ErrorSistema::Desbordamiento)?,
+                None => 0,
+            };
+            direcciones.push(Direccion { id, contenido });
+            self.direcciones.insert(comprador, &direcciones);
+
+            if !self.direccion_predeterminada.contains(comprador) {
+                self.direccion_predeterminada.insert(comprador, &id);
+            }
+            Ok(id)
+        }
+
+        #[ink(message)]
+        pub fn eliminar_direccion(&mut self, direccion: u32) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self._eliminar_direccion(direccion, id)
+        }
+
+        fn _eliminar_direccion(&mut self, direccion: u32, comprador: AccountId) -> Result<(), ErrorSistema> {
+            let mut direcciones = self.direcciones.get(comprador).unwrap_or_default();
+            let posicion = direcciones.iter().position(|d| d.id == direccion).ok_or(ErrorSistema::DireccionNoExiste)?;
+            direcciones.remove(posicion);
+            self.direcciones.insert(comprador, &direcciones);
+
+            //Si era la predeterminada, pasa a serlo la primera que quede (si queda alguna).
+            if self.direccion_predeterminada.get(comprador) == Some(direccion) {
+                if let Some(d) = direcciones.first() {
+                    self.direccion_predeterminada.insert(comprador, &d.id);
+                } else {
+                    self.direccion_predeterminada.remove(comprador);
+                }
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn elegir_direccion_predeterminada(&mut self, direccion: u32) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self._get_direccion(id, direccion)?;
+            self.direccion_predeterminada.insert(id, &direccion);
+            Ok(())
+        }
+
+        /// Devuelve las direcciones del caller. Las direcciones de un comprador solo las puede listar él mismo.
+        #[ink(message)]
+        pub fn listar_direcciones(&self) -> Vec<Direccion> {
+            let id = self.env().caller();
+            self.direcciones.get(id).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_direccion_predeterminada(&self) -> Result<Direccion, ErrorSistema> {
+            let id = self.env().caller();
+            let direccion = self.direccion_predeterminada.get(id).ok_or(ErrorSistema::DireccionNoExiste)?;
+            self._get_direccion(id, direccion)
+        }
+
+        fn _get_direccion(&self, comprador: AccountId, direccion: u32) -> Result<Direccion, ErrorSistema> {
+            self.direcciones.get(comprador)
+                .unwrap_or_default()
+                .into_iter()
+                .find(|d| d.id == direccion)
+                .ok_or(ErrorSistema::DireccionNoExiste)
+        }
+
+        //Funciones asociadas a categorías.
+
+        /// Crea una categoría. Solo el admin puede hacerlo. Si se indica un padre, este tiene que existir.
+        #[ink(message)]
+        pub fn crear_categoria(&mut self, nombre: String, padre: Option<u32>) -> Result<u32, ErrorSistema> {
+            self._marcar_actividad();
+            self._crear_categoria(nombre, padre)
+        }
+
+        fn _crear_categoria(&mut self, nombre: String, padre: Option<u32>) -> Result<u32, ErrorSistema> {
+            self._solo_admin()?;
+            if nombre.is_empty() {
+                return Err(ErrorSistema::DatosInvalidos);
+            }
+            if let Some(padre) = padre {
+                self._get_categoria(padre)?;
+            }
+
+            let id = self.cantidad_categorias;
+            self.cantidad_categorias = id.checked_add(1).ok_or(ErrorSistema::Desbordamiento)?;
+            self.categorias.insert(id, &Categoria { id, nombre, padre });
+            Ok(id)
+        }
+
+        #[ink(message)]
+        pub fn get_categoria(&self, id: u32) -> Result<Categoria, ErrorSistema> {
+            self._get_categoria(id)
+        }
+
+        fn _get_categoria(&self, id: u32) -> Result<Categoria, ErrorSistema> {
+            self.categorias.get(id).ok_or(ErrorSistema::CategoriaNoExiste)
+        }
+
+        /// Devuelve las categorías hijas de `padre` (o las categorías raíz si `padre` es None).
+        #[ink(message)]
+        pub fn listar_subcategorias(&self, padre: Option<u32>) -> Vec<Categoria> {
+            (0..self.cantidad_categorias)
+                .filter_map(|id| self.categorias.get(id))
+                .filter(|categoria| categoria.padre == padre)
+                .collect()
+        }
+
+        //Funciones asociadas a publicaciones.
+
+        #[ink(message)]
+        pub fn crear_publicacion(&mut self, nombre: String, descripcion: String, precio: Balance, stock: u32, categoria: u32) -> Result<u32, ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self._crear_publicacion(nombre, descripcion, precio, stock, categoria, id)
+        }
+
+        fn _crear_publicacion(&mut self, nombre: String, descripcion: String, precio: Balance, stock: u32, categoria: u32, vendedor: AccountId) -> Result<u32, ErrorSistema> {
+            self._existe_usuario(vendedor)?;
+            if !self._tiene_permiso(vendedor, Permiso::Publicar) {
+                return Err(ErrorSistema::NoEsVendedor);
+            }
+            if self.suspendidos.contains(vendedor) {
+                return Err(ErrorSistema::VendedorSuspendido);
+            }
+            if nombre.is_empty() {
+                return Err(ErrorSistema::DatosInvalidos);
+            }
+            // Toda publicación tiene que referenciar una categoría válida.
+            self._get_categoria(categoria)?;
+            //Se suma lo que ya tiene publicado para que no alcance con partir una publicación en varias.
+            let valor = self.valor_publicado.get(vendedor).unwrap_or(0).saturating_add(precio.saturating_mul(Balance::from(stock)));
+            if let Some(regla) = &self.umbral_verificacion {
+                if valor > regla.valor_maximo && self._nivel_verificacion(vendedor)? < regla.nivel_minimo {
+                    return Err(ErrorSistema::VerificacionRequerida);
+                }
+            }
+
+            let id = self.cantidad_publicaciones;
+            self.cantidad_publicaciones = id.checked_add(1).ok_or(ErrorSistema::Desbordamiento)?;
+            self.publicaciones.insert(id, &Publicacion { id, vendedor, nombre, descripcion, precio, stock, categoria, oculta: false });
+
+            self._agregar_id_a_lista(Lista::ProductosDe(categoria), id)?;
+
+            self._agregar_id_a_lista(Lista::PublicacionesDe(vendedor), id)?;
+            self.valor_publicado.insert(vendedor, &valor);
+            self.env().emit_event(NuevaPublicacion { vendedor, id });
+            Ok(id)
+        }
+
+        #[ink(message)]
+        pub fn get_publicacion(&self, id: u32) -> Result<Publicacion, ErrorSistema> {
+            self.publicaciones.get(id).ok_or(ErrorSistema::PublicacionNoExiste)
+        }
+
+        /// Lista las publicaciones visibles de una categoría de a páginas. La primera página es la 0.
+        /// Una página puede traer menos de `por_pagina` publicaciones, porque las que no se ven se descartan.
+        #[ink(message)]
+        pub fn listar_por_categoria(&self, categoria: u32, pagina: u32, por_pagina: u32) -> Result<Vec<Publicacion>, ErrorSistema> {
+            self._get_categoria(categoria)?;
+            Ok(self._visibles(&self._pagina_de_ids(Lista::ProductosDe(categoria), pagina, por_pagina)))
+        }
+
+        //Las publicaciones ocultas o de vendedores suspendidos no se listan.
+        fn _es_visible(&self, publicacion: &Publicacion) -> bool {
+            !publicacion.oculta && !self.suspendidos.contains(publicacion.vendedor)
+        }
+
+        //Se filtra después de paginar, así cada página lee a lo sumo `por_pagina` publicaciones.
+        fn _visibles(&self, ids: &[u32]) -> Vec<Publicacion> {
+            ids.iter()
+                .filter_map(|id| self.publicaciones.get(id))
+                .filter(|publicacion| self._es_visible(publicacion))
+                .collect()
+        }
+
+        /// El vendedor da de baja una publicación. También se la saca de los favoritos de todos.
+        #[ink(message)]
+        pub fn retirar_publicacion(&mut self, id: u32) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let publicacion = self.get_publicacion(id)?;
+            if publicacion.vendedor != self.env().caller() {
+                return Err(ErrorSistema::NoEsVendedor);
+            }
+            self._retirar_publicacion(publicacion);
+            Ok(())
+        }
+
+        fn _retirar_publicacion(&mut self, publicacion: Publicacion) {
+            let id = publicacion.id;
+            self.publicaciones.remove(id);
+
+            self._quitar_id_de_lista(Lista::ProductosDe(publicacion.categoria), id);
+
+            self._quitar_id_de_lista(Lista::PublicacionesDe(publicacion.vendedor), id);
+            let valor = self.valor_publicado.get(publicacion.vendedor).unwrap_or(0)
+                .saturating_sub(publicacion.precio.saturating_mul(Balance::from(publicacion.stock)));
+            self.valor_publicado.insert(publicacion.vendedor, &valor);
+
+            self.contador_reportes.remove(ObjetoReporte::Publicacion(id));
+            self.promociones.remove(id);
+            self._quitar_id_de_lista(Lista::Promocionadas, id);
+            //No se recorre a quienes la guardaron (podrían ser demasiados): cada lista de favoritos
+            //deja de mostrarla enseguida y la borra la próxima vez que su dueño la modifica.
+            self.contador_favoritos.remove(id);
+        }
+
+        //Funciones asociadas a favoritos.
+
+        #[ink(message)]
+        pub fn agregar_favorito(&mut self, producto: u32) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self._existe_usuario(id)?;
+            if !self._tiene_permiso(id, Permiso::Comprar) {
+                return Err(ErrorSistema::NoEsComprador);
+            }
+            self.get_publicacion(producto)?;
+            if self.guardados.contains((producto, id)) {
+                return Err(ErrorSistema::YaEsFavorito);
+            }
+            let mut favoritos = self._limpiar_favoritos(id);
+            favoritos.push(producto);
+            self.favoritos.insert(id, &favoritos);
+            self.guardados.insert((producto, id), &());
+            let cantidad = self.contador_favoritos.get(producto).unwrap_or(0);
+            self.contador_favoritos.insert(producto, &cantidad.checked_add(1).ok_or(ErrorSistema::Desbordamiento)?);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn quitar_favorito(&mut self, producto: u32) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            if self.guardados.take((producto, id)).is_none() {
+                return Err(ErrorSistema::NoEsFavorito);
+            }
+            let mut favoritos = self._limpiar_favoritos(id);
+            favoritos.retain(|guardado| *guardado != producto);
+            self.favoritos.insert(id, &favoritos);
+            if let Some(cantidad) = self.contador_favoritos.get(producto) {
+                self.contador_favoritos.insert(producto, &cantidad.saturating_sub(1));
+            }
+            Ok(())
+        }
+
+        //Devuelve los favoritos del usuario sin los productos que ya se retiraron.
+        fn _limpiar_favoritos(&mut self, id: AccountId) -> Vec<u32> {
+            let (vigentes, retirados): (Vec<u32>, Vec<u32>) = self.favoritos.get(id).unwrap_or_default()
+                .into_iter()
+                .partition(|producto| self.publicaciones.contains(producto));
+            for producto in retirados {
+                self.guardados.remove((producto, id));
+            }
+            vigentes
+        }
+
+        /// Lista los favoritos del caller de a páginas. La primera página es la 0.
+        /// Una página puede traer menos de `por_pagina` publicaciones, porque las retiradas o que no se ven se descartan.
+        #[ink(message)]
+        pub fn listar_favoritos(&self, pagina: u32, por_pagina: u32) -> Vec<Publicacion> {
+            let favoritos = self.favoritos.get(self.env().caller()).unwrap_or_default();
+            self._visibles(Self::_paginar(&favoritos, pagina, por_pagina))
+        }
+
+        /// Cuántos usuarios guardaron el producto. Sólo lo puede ver su vendedor.
+        #[ink(message)]
+        pub fn cantidad_favoritos(&self, producto: u32) -> Result<u32, ErrorSistema> {
+            let publicacion = self.get_publicacion(producto)?;
+            if publicacion.vendedor != self.env().caller() {
+                return Err(ErrorSistema::NoEsVendedor);
+            }
+            Ok(self.contador_favoritos.get(producto).unwrap_or(0))
+        }
+
+        //Funciones asociadas a seguir vendedores.
+
+        #[ink(message)]
+        pub fn seguir_vendedor(&mut self, vendedor: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            self._existe_usuario(id)?;
+            if id == vendedor {
+                return Err(ErrorSistema::DatosInvalidos);
+            }
+            if !self._es_vendedor(vendedor)? {
+                return Err(ErrorSistema::NoEsVendedor);
+            }
+            if self.posiciones_lista.contains((Lista::Seguidores(vendedor), id)) {
+                return Err(ErrorSistema::YaSigue);
+            }
+            self._agregar_a_lista(Lista::Seguidos(id), vendedor)?;
+            self._agregar_a_lista(Lista::Seguidores(vendedor), id)
+        }
+
+        #[ink(message)]
+        pub fn dejar_de_seguir(&mut self, vendedor: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let id = self.env().caller();
+            if !self._quitar_de_lista(Lista::Seguidores(vendedor), id) {
+                return Err(ErrorSistema::NoSigue);
+            }
+            self._quitar_de_lista(Lista::Seguidos(id), vendedor);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn cantidad_seguidores(&self, vendedor: AccountId) -> u32 {
+            self.largo_lista.get(Lista::Seguidores(vendedor)).unwrap_or(0)
+        }
+
+        /// Lista a quiénes sigue `cuenta`, de a páginas. La primera página es la 0.
+        #[ink(message)]
+        pub fn listar_seguidos(&self, cuenta: AccountId, pagina: u32, por_pagina: u32) -> Vec<AccountId> {
+            self._pagina_de_lista(Lista::Seguidos(cuenta), pagina, por_pagina)
+        }
+
+        //Funciones asociadas a reportes y moderación.
+
+        #[ink(message)]
+        pub fn reportar_publicacion(&mut self, id: u32, motivo: MotivoReporte) -> Result<u32, ErrorSistema> {
+            self._marcar_actividad();
+            let publicacion = self.get_publicacion(id)?;
+            let reporte = self._reportar(ObjetoReporte::Publicacion(id), motivo)?;
+
+            let reportes = self.contador_reportes.get(ObjetoReporte::Publicacion(id)).unwrap_or(0);
+            if !publicacion.oculta && reportes >= self.umbral_reportes {
+                self._ocultar_publicacion(publicacion, None);
+            }
+            Ok(reporte)
+        }
+
+        #[ink(message)]
+        pub fn reportar_usuario(&mut self, cuenta: AccountId, motivo: MotivoReporte) -> Result<u32, ErrorSistema> {
+            self._marcar_actividad();
+            self._existe_usuario(cuenta)?;
+            if cuenta == self.env().caller() {
+                return Err(ErrorSistema::DatosInvalidos);
+            }
+            self._reportar(ObjetoReporte::Usuario(cuenta), motivo)
+        }
+
+        //Cada cuenta registrada puede reportar una sola vez lo mismo. Devuelve el id del reporte.
+        fn _reportar(&mut self, objeto: ObjetoReporte, motivo: MotivoReporte) -> Result<u32, ErrorSistema> {
+            let autor = self.env().caller();
+            self._existe_usuario(autor)?;
+            if self.reportantes.contains((objeto.clone(), autor)) {
+                return Err(ErrorSistema::YaReportado);
+            }
+            self.reportantes.insert((objeto.clone(), autor), &());
+            let reportes = self.contador_reportes.get(&objeto).unwrap_or(0);
+            self.contador_reportes.insert(&objeto, &reportes.checked_add(1).ok_or(ErrorSistema::Desbordamiento)?);
+
+            let id = self.cantidad_reportes;
+            self.cantidad_reportes = id.checked_add(1).ok_or(ErrorSistema::Desbordamiento)?;
+            self.reportes.insert(id, &Reporte { id, objeto: objeto.clone(), autor, motivo });
+            self._agregar_id_a_lista(Lista::ReportesAbiertos, id)?;
+            self._agregar_id_a_lista(Lista::ReportesDe(autor), id)?;
+            if let ObjetoReporte::Usuario(cuenta) = objeto {
+                self._agregar_id_a_lista(Lista::ReportesSobre(cuenta), id)?;
+            }
+            self.env().emit_event(ReporteCreado { id, objeto, motivo });
+            Ok(id)
+        }
+
+        /// Reportes que todavía no cerró ningún moderador, de a páginas. La primera página es la 0.
+        #[ink(message)]
+        pub fn listar_reportes_pendientes(&self, pagina: u32, por_pagina: u32) -> Result<Vec<Reporte>, ErrorSistema> {
+            self._solo_moderador()?;
+            Ok(self._pagina_de_ids(Lista::ReportesAbiertos, pagina, por_pagina)
+                .into_iter()
+                .filter_map(|id| self.reportes.get(id))
+                .collect())
+        }
+
+        /// Saca el reporte de la cola, se haya tomado alguna acción o no.
+        #[ink(message)]
+        pub fn cerrar_reporte(&mut self, id: u32) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let moderador = self._solo_moderador()?;
+            if !self._quitar_id_de_lista(Lista::ReportesAbiertos, id) {
+                return Err(ErrorSistema::ReporteNoExiste);
+            }
+            if let Some(Reporte { objeto: ObjetoReporte::Usuario(cuenta), .. }) = self.reportes.get(id) {
+                self._quitar_id_de_lista(Lista::ReportesSobre(cuenta), id);
+            }
+            self.env().emit_event(ReporteCerrado { id, moderador });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn ocultar_publicacion(&mut self, id: u32) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let moderador = self._solo_moderador()?;
+            let publicacion = self.get_publicacion(id)?;
+            self._ocultar_publicacion(publicacion, Some(moderador));
+            Ok(())
+        }
+
+        fn _ocultar_publicacion(&mut self, mut publicacion: Publicacion, moderador: Option<AccountId>) {
+            publicacion.oculta = true;
+            self.publicaciones.insert(publicacion.id, &publicacion);
+            self.env().emit_event(PublicacionOcultada { id: publicacion.id, moderador });
+        }
+
+        /// Vuelve a mostrar una publicación oculta. Los reportes anteriores dejan de contar para el ocultamiento automático.
+        #[ink(message)]
+        pub fn restaurar_publicacion(&mut self, id: u32) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let moderador = self._solo_moderador()?;
+            let mut publicacion = self.get_publicacion(id)?;
+            publicacion.oculta = false;
+            self.publicaciones.insert(id, &publicacion);
+            //Quienes ya la reportaron no pueden volver a hacerlo; el contador arranca de nuevo.
+            self.contador_reportes.remove(ObjetoReporte::Publicacion(id));
+            self.env().emit_event(PublicacionRestaurada { id, moderador });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn advertir_usuario(&mut self, cuenta: AccountId, motivo: MotivoReporte) -> Result<u32, ErrorSistema> {
+            self._marcar_actividad();
+            let moderador = self._solo_moderador()?;
+            self._existe_usuario(cuenta)?;
+            let advertencias = self.advertencias.get(cuenta).unwrap_or(0).checked_add(1).ok_or(ErrorSistema::Desbordamiento)?;
+            self.advertencias.insert(cuenta, &advertencias);
+            self.env().emit_event(UsuarioAdvertido { cuenta, moderador, motivo });
+            Ok(advertencias)
+        }
+
+        #[ink(message)]
+        pub fn get_advertencias(&self, cuenta: AccountId) -> u32 {
+            self.advertencias.get(cuenta).unwrap_or(0)
+        }
+
+        /// Un vendedor suspendido no puede publicar y sus publicaciones dejan de listarse hasta que se lo reactive.
+        #[ink(message)]
+        pub fn suspender_vendedor(&mut self, cuenta: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let moderador = self._solo_moderador()?;
+            if !self._es_vendedor(cuenta)? {
+                return Err(ErrorSistema::NoEsVendedor);
+            }
+            if self.suspendidos.contains(cuenta) {
+                return Err(ErrorSistema::VendedorSuspendido);
+            }
+            self.suspendidos.insert(cuenta, &());
+            self._registrar_cambio(cuenta, TipoCambio::Suspendido)?;
+            self.env().emit_event(VendedorSuspendido { cuenta, moderador });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn reactivar_vendedor(&mut self, cuenta: AccountId) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            let moderador = self._solo_moderador()?;
+            if self.suspendidos.take(cuenta).is_none() {
+                return Err(ErrorSistema::DatosInvalidos);
+            }
+            self._registrar_cambio(cuenta, TipoCambio::Reactivado)?;
+            self.env().emit_event(VendedorReactivado { cuenta, moderador });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn configurar_umbral_reportes(&mut self, umbral: u32) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            if umbral == 0 {
+                return Err(ErrorSistema::DatosInvalidos);
+            }
+            self.umbral_reportes = umbral;
+            Ok(())
+        }
+
+        //Funciones asociadas a publicaciones promocionadas.
+
+        const MAXIMA_DURACION_PROMOCION: u32 = 30 * 24; //Un mes, con la unidad por defecto de una hora.
+
+        /// Promociona `producto` durante `duracion` unidades de tiempo, hasta 720. Todo lo pagado va a la tesorería,
+        /// y lo pagado por unidad es la oferta con la que se ordenan los destacados.
+        /// Hasta que el admin configure un precio no se puede promocionar.
+        #[ink(message, payable)]
+        pub fn promocionar_publicacion(&mut self, producto: u32, duracion: u32) -> Result<Timestamp, ErrorSistema> {
+            self._marcar_actividad();
+            let publicacion = self.get_publicacion(producto)?;
+            if publicacion.vendedor != self.env().caller() {
+                return Err(ErrorSistema::NoEsVendedor);
+            }
+            if self.suspendidos.contains(publicacion.vendedor) {
+                return Err(ErrorSistema::VendedorSuspendido);
+            }
+            if publicacion.oculta {
+                return Err(ErrorSistema::PublicacionOculta);
+            }
+            if self.precio_promocion == 0 {
+                return Err(ErrorSistema::PromocionesDeshabilitadas);
+            }
+            if duracion == 0 || duracion > Self::MAXIMA_DURACION_PROMOCION {
+                return Err(ErrorSistema::DatosInvalidos);
+            }
+            let ahora = self.env().block_timestamp();
+            if self.promociones.get(producto).is_some_and(|promocion| promocion.vencimiento > ahora) {
+                return Err(ErrorSistema::PromocionActiva);
+            }
+
+            let pagado = self.env().transferred_value();
+            let minimo = self.precio_promocion.checked_mul(Balance::from(duracion)).ok_or(ErrorSistema::Desbordamiento)?;
+            if pagado < minimo {
+                return Err(ErrorSistema::PagoInsuficiente);
+            }
+            let vencimiento = self.unidad_promocion.checked_mul(Timestamp::from(duracion))
+                .and_then(|plazo| ahora.checked_add(plazo))
+                .ok_or(ErrorSistema::Desbordamiento)?;
+            self.tesoreria = self.tesoreria.checked_add(pagado).ok_or(ErrorSistema::Desbordamiento)?;
+
+            //Aprovecho para sacar las promociones vencidas, así la lista solo crece con las vigentes.
+            let largo = self.largo_lista.get(Lista::Promocionadas).unwrap_or(0);
+            let vencidas: Vec<u32> = self._pagina_de_ids(Lista::Promocionadas, 0, largo)
+                .into_iter()
+                .filter(|id| self.promociones.get(id).is_none_or(|promocion| promocion.vencimiento <= ahora))
+                .collect();
+            for id in vencidas {
+                self.promociones.remove(id);
+                self._quitar_id_de_lista(Lista::Promocionadas, id);
+            }
 
-    impl Sistema {
-        /// Constructor that initializes the `bool` value to the given `init_value`.
-        #[ink(constructor)]
-        pub fn new(init_value: bool) -> Self {
-            Self { value: init_value, usuarios: Mapping::new() }
+            self.promociones.insert(producto, &Promocion { oferta: pagado / Balance::from(duracion), vencimiento });
+            self._agregar_id_a_lista(Lista::Promocionadas, producto)?;
+            Ok(vencimiento)
         }
 
-        /// Constructor that initializes the `bool` value to `false`.
-        ///
-        /// Constructors can delegate to other constructors.
-        #[ink(constructor)]
-        pub fn default() -> Self {
-            Self::new(Default::default())
+        /// Publicaciones con promoción vigente, de mayor a menor oferta, de a páginas. La primera página es la 0.
+        #[ink(message)]
+        pub fn listar_destacados(&self, pagina: u32, por_pagina: u32) -> Vec<Publicacion> {
+            let ahora = self.env().block_timestamp();
+            let largo = self.largo_lista.get(Lista::Promocionadas).unwrap_or(0);
+            let mut destacados: Vec<(Balance, Publicacion)> = self._pagina_de_ids(Lista::Promocionadas, 0, largo)
+                .into_iter()
+                .filter_map(|id| Some((self.promociones.get(id)?, self.publicaciones.get(id)?)))
+                .filter(|(promocion, publicacion)| promocion.vencimiento > ahora && self._es_visible(publicacion))
+                .map(|(promocion, publicacion)| (promocion.oferta, publicacion))
+                .collect();
+            //La lista no guarda el orden de llegada: a igual oferta queda primero la publicación más vieja.
+            destacados.sort_by_key(|(oferta, publicacion)| (core::cmp::Reverse(*oferta), publicacion.id));
+            Self::_paginar(&destacados, pagina, por_pagina)
+                .iter()
+                .map(|(_, publicacion)| publicacion.clone())
+                .collect()
         }
 
-        /// A message that can be called on instantiated contracts.
-        /// This one flips the value of the stored `bool` from `true`
-        /// to `false` and vice versa.
         #[ink(message)]
-        pub fn flip(&mut self) {
-            self.value = !self.value;
+        pub fn configurar_promociones(&mut self, precio: Balance, unidad: Timestamp) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            if unidad == 0 {
+                return Err(ErrorSistema::DatosInvalidos);
+            }
+            self.precio_promocion = precio;
+            self.unidad_promocion = unidad;
+            Ok(())
         }
 
-        /// Simply returns the current value of our `bool`.
         #[ink(message)]
-        pub fn get(&self) -> bool {
-            self.value
+        pub fn get_tesoreria(&self) -> Balance {
+            self.tesoreria
         }
 
-        //Verificadores del sistema.
-        fn _existe_usuario(&self, id: AccountId) -> Result<bool, ErrorSistema> {
-            if self.usuarios.get(&id).is_some() {
-                Ok(true)
-            } else {
-                Err(ErrorSistema::UsuarioNoExiste)
+        #[ink(message)]
+        pub fn retirar_tesoreria(&mut self, destino: AccountId, monto: Balance) -> Result<(), ErrorSistema> {
+            self._marcar_actividad();
+            self._solo_admin()?;
+            if monto > self.tesoreria {
+                return Err(ErrorSistema::FondosInsuficientes);
             }
+            self.tesoreria -= monto;
+            self.env().transfer(destino, monto).map_err(|_| ErrorSistema::TransferenciaFallida)?;
+            Ok(())
         }
 
-        #[ink(message)]
-        pub fn es_vendedor(&self) -> Result<bool, ErrorSistema> { //Duda: Está bien recibirlo como parámetro al id o lo tengo que obtener del caller?
-            //Duda: Debería preguntar acá o en el privado si el usuario existe?
+        //Funciones asociadas a las listas guardadas en Mappings.
 
-            let id = self.env().caller(); //Está bien esto? 
-            self._es_vendedor(id)
+        //Agrega la cuenta al final de la lista, si no estaba.
+        fn _agregar_a_lista(&mut self, lista: Lista, cuenta: AccountId) -> Result<(), ErrorSistema> {
+            if self.posiciones_lista.contains((lista, cuenta)) {
+                return Ok(());
+            }
+            let largo = self.largo_lista.get(lista).unwrap_or(0);
+            self.elementos_lista.insert((lista, largo), &cuenta);
+            self.posiciones_lista.insert((lista, cuenta), &largo);
+            self.largo_lista.insert(lista, &largo.checked_add(1).ok_or(ErrorSistema::Desbordamiento)?);
+            Ok(())
         }
 
-        fn _es_vendedor(&self, id: AccountId) -> Result<bool, ErrorSistema> {
-            //Si existe el usuario
-                //lo encuentro
-                //y verifico si es vendedor o ambos.
-            //Si no existo -> ErrorSistema::UsuarioNoExiste
-
-            if (self._existe_usuario(id)).is_err() {
-                return Err(ErrorSistema::UsuarioNoExiste);
-            } else {
-                //Busco al usuario y verifico su rol.
-                let user = self.usuarios.get(&id);
-                match user.unwrap().rol {
-                    Rol::Vendedor | Rol::Ambos => Ok(true),
-                    _ => Ok(false),
-                }   
+        //Pone la última cuenta en el lugar de la que se saca, así no hay que correr las demás.
+        //Por eso la lista no conserva el orden de llegada. Devuelve si la cuenta estaba.
+        fn _quitar_de_lista(&mut self, lista: Lista, cuenta: AccountId) -> bool {
+            let Some(posicion) = self.posiciones_lista.take((lista, cuenta)) else {
+                return false;
+            };
+            let ultima = self.largo_lista.get(lista).unwrap_or(0).saturating_sub(1);
+            if let Some(movida) = self.elementos_lista.take((lista, ultima)) {
+                if posicion != ultima {
+                    self.elementos_lista.insert((lista, posicion), &movida);
+                    self.posiciones_lista.insert((lista, movida), &posicion);
+                }
             }
+            self.largo_lista.insert(lista, &ultima);
+            true
         }
 
-        //Mismas dudas que en es_vendedor.
-        #[ink(message)]
-        pub fn es_comprador(&self) -> Result<bool, ErrorSistema> { 
-            let id = self.env().caller(); 
-            self._es_comprador(id)
+        //Pone `nueva` en el lugar de `vieja`, si estaba en la lista.
+        fn _reemplazar_en_lista(&mut self, lista: Lista, vieja: AccountId, nueva: AccountId) {
+            if let Some(posicion) = self.posiciones_lista.take((lista, vieja)) {
+                self.elementos_lista.insert((lista, posicion), &nueva);
+                self.posiciones_lista.insert((lista, nueva), &posicion);
+            }
         }
 
-        fn _es_comprador(&self, id: AccountId) -> Result<bool, ErrorSistema> {
-            //Si existe el usuario
-                //lo encuentro
-                //y verifico si es comprador o ambos.
-            //Si no existo -> ErrorSistema::UsuarioNoExiste
-
-            if (self._existe_usuario(id)).is_err() {
-                return Err(ErrorSistema::UsuarioNoExiste);
-            } else {
-                //Busco al usuario y verifico su rol.
-                let user = self.usuarios.get(&id);
-                match user.unwrap().rol {
-                    Rol::Comprador | Rol::Ambos => Ok(true),
-                    _ => Ok(false),
-                }   
+        //Borra la lista entera y devuelve lo que tenía.
+        fn _vaciar_lista(&mut self, lista: Lista) -> Vec<AccountId> {
+            let largo = self.largo_lista.take(lista).unwrap_or(0);
+            let mut cuentas = Vec::new();
+            for posicion in 0..largo {
+                if let Some(cuenta) = self.elementos_lista.take((lista, posicion)) {
+                    self.posiciones_lista.remove((lista, cuenta));
+                    cuentas.push(cuenta);
+                }
             }
+            cuentas
         }
 
-        //Funciones asociadas a usuarios. 
+        //Pasa todo el contenido de `origen` al final de `destino`, que puede no estar vacía. Devuelve lo que se movió.
+        fn _mover_lista(&mut self, origen: Lista, destino: Lista) -> Result<Vec<AccountId>, ErrorSistema> {
+            let cuentas = self._vaciar_lista(origen);
+            for cuenta in cuentas.iter() {
+                self._agregar_a_lista(destino, *cuenta)?;
+            }
+            Ok(cuentas)
+        }
 
-        #[ink(message)]
-        pub fn registrar_usuario(&mut self, nombre:String, apellido:String, email:String, rol:Rol) -> Result<(), ErrorSistema> {
-            let id = self.env().caller(); // Se obtiene el AccountId del usuario que llama a la función.
+        fn _pagina_de_lista(&self, lista: Lista, pagina: u32, por_pagina: u32) -> Vec<AccountId> {
+            self._posiciones_de_pagina(lista, pagina, por_pagina)
+                .filter_map(|posicion| self.elementos_lista.get((lista, posicion)))
+                .collect()
+        }
 
-            self._registrar_usuario(nombre, apellido, email, rol, id)?;
-            Ok(())
+        fn _posiciones_de_pagina(&self, lista: Lista, pagina: u32, por_pagina: u32) -> core::ops::Range<u32> {
+            let largo = self.largo_lista.get(lista).unwrap_or(0);
+            let inicio = pagina.saturating_mul(por_pagina).min(largo);
+            let fin = inicio.saturating_add(por_pagina).min(largo);
+            inicio..fin
         }
 
+        //Las mismas operaciones para listas de ids (reportes, publicaciones).
 
-        //Siempre lo marca como ya registrado (por más de que no lo esté) ????
-        fn _registrar_usuario(&mut self, nombre:String, apellido:String, email:String, rol:Rol, id:AccountId) -> Result<(), ErrorSistema>{
-            // Chequear que el usuario a registrar no exista en el sistema. (Solo registrar usuarios nuevos)
-            if self.usuarios.get(&id).is_some() { //Busca match en el mapping.
-                return Err(ErrorSistema::UsuarioYaRegistrado);
-            }                
-            
-            self.usuarios.insert(id, &Usuario {nombre, apellido, email, id, rol});
+        fn _agregar_id_a_lista(&mut self, lista: Lista, id: u32) -> Result<(), ErrorSistema> {
+            if self.posiciones_lista_ids.contains((lista, id)) {
+                return Ok(());
+            }
+            let largo = self.largo_lista.get(lista).unwrap_or(0);
+            self.elementos_lista_ids.insert((lista, largo), &id);
+            self.posiciones_lista_ids.insert((lista, id), &largo);
+            self.largo_lista.insert(lista, &largo.checked_add(1).ok_or(ErrorSistema::Desbordamiento)?);
             Ok(())
         }
 
-        #[ink(message)]
-        pub fn agregar_rol(&mut self, rol: Rol) -> Result<(), ErrorSistema> {
-            let id = self.env().caller(); // Se obtiene el AccountId del usuario que llama a la función.
-
-            self._agregar_rol(rol, id)
+        fn _quitar_id_de_lista(&mut self, lista: Lista, id: u32) -> bool {
+            let Some(posicion) = self.posiciones_lista_ids.take((lista, id)) else {
+                return false;
+            };
+            let ultima = self.largo_lista.get(lista).unwrap_or(0).saturating_sub(1);
+            if let Some(movido) = self.elementos_lista_ids.take((lista, ultima)) {
+                if posicion != ultima {
+                    self.elementos_lista_ids.insert((lista, posicion), &movido);
+                    self.posiciones_lista_ids.insert((lista, movido), &posicion);
+                }
+            }
+            self.largo_lista.insert(lista, &ultima);
+            true
         }
 
-        fn _agregar_rol(&mut self, rol: Rol, id: AccountId) -> Result<(), ErrorSistema> { //Hacer un agregar para cada rol distinto.
-            // Verifica si el usuario existe.
-            if let Some(mut user) = self.usuarios.get(&id) {  
-                user.agregar_rol(rol.clone())?; //Llama a la función del usuario que modifica su rol. (Lo delega)
-                self.usuarios.insert(&id, &user); //Lo guardo modificado en le mapping.
-                Ok(())
-            } else {
-                Err(ErrorSistema::UsuarioNoExiste)
+        fn _vaciar_lista_ids(&mut self, lista: Lista) -> Vec<u32> {
+            let largo = self.largo_lista.take(lista).unwrap_or(0);
+            let mut ids = Vec::new();
+            for posicion in 0..largo {
+                if let Some(id) = self.elementos_lista_ids.take((lista, posicion)) {
+                    self.posiciones_lista_ids.remove((lista, id));
+                    ids.push(id);
+                }
             }
+            ids
         }
 
+        fn _mover_lista_ids(&mut self, origen: Lista, destino: Lista) -> Result<Vec<u32>, ErrorSistema> {
+            let ids = self._vaciar_lista_ids(origen);
+            for id in ids.iter() {
+                self._agregar_id_a_lista(destino, *id)?;
+            }
+            Ok(ids)
+        }
 
-        fn _get_user(&self, id:AccountId)-> Result<Usuario, ErrorSistema>{
+        fn _pagina_de_ids(&self, lista: Lista, pagina: u32, por_pagina: u32) -> Vec<u32> {
+            self._posiciones_de_pagina(lista, pagina, por_pagina)
+                .filter_map(|posicion| self.elementos_lista_ids.get((lista, posicion)))
+                .collect()
+        }
 
-            if let Some(user) = self.usuarios.get(&id) {
-                Ok(user.clone())
-            } else {
-                Err(ErrorSistema::UsuarioNoExiste)
-            }
+        fn _paginar<T>(elementos: &[T], pagina: u32, por_pagina: u32) -> &[T] {
+            let inicio = (pagina as usize).saturating_mul(por_pagina as usize).min(elementos.len());
+            let fin = inicio.saturating_add(por_pagina as usize).min(elementos.len());
+            &elementos[inicio..fin]
         }
     }
 
@@ -206,20 +2569,66 @@ mod usuarios_sistema {
         //registrarse. ?? Acá sí que no me quedó clara la parte de delegar.
         //pub fn crear_publicación
         //pub fn agregar_a_orden_compra
+
+        //Las fechas las completa el sistema al registrarlo.
+        fn nuevo(nombre: String, apellido: String, email: String, id: AccountId, roles: Roles, deposito: Balance) -> Usuario {
+            Usuario { nombre, apellido, email, id, roles, verificacion: None, deposito, fecha_registro: FechaBloque::default(), ultima_actividad: 0, handle: None }
+        }
         
-        pub fn agregar_rol(&mut self, rol: Rol) -> Result<(), ErrorSistema> { //Hacer un agregar para cada rol distinto..
-            if self.rol == rol {
+        pub fn agregar_rol(&mut self, rol: Rol) -> Result<(), ErrorSistema> {
+            if self.roles.contiene(&rol) {
                 return Err(ErrorSistema::RolYaEnUso);
             }
             // Agrega el nuevo rol al usuario.
-            self.rol = match (self.rol.clone(), rol.clone()) {
-                (Rol::Comprador, Rol::Vendedor) | (Rol::Vendedor, Rol::Comprador) => Rol::Ambos,
-                _ => rol, //está de más?
-            };
+            self.roles.agregar(&rol);
             Ok(())
         }
     }
 
+    impl Roles {
+        const ROLES: [Rol; 6] = [Rol::Comprador, Rol::Vendedor, Rol::Moderador, Rol::Arbitro, Rol::Repartidor, Rol::Admin];
+
+        fn bits(rol: &Rol) -> u8 {
+            match rol {
+                Rol::Comprador => 1,
+                Rol::Vendedor => 1 << 1,
+                Rol::Ambos => 1 | 1 << 1,
+                Rol::Moderador => 1 << 2,
+                Rol::Arbitro => 1 << 3,
+                Rol::Repartidor => 1 << 4,
+                Rol::Admin => 1 << 5,
+            }
+        }
+
+        pub fn contiene(&self, rol: &Rol) -> bool {
+            self.0 & Self::bits(rol) == Self::bits(rol)
+        }
+
+        pub fn agregar(&mut self, rol: &Rol) {
+            self.0 |= Self::bits(rol);
+        }
+
+        pub fn quitar(&mut self, rol: &Rol) {
+            self.0 &= !Self::bits(rol);
+        }
+
+        /// Devuelve cada rol por separado (nunca `Ambos`).
+        pub fn listar(&self) -> Vec<Rol> {
+            Self::ROLES.into_iter().filter(|rol| self.contiene(rol)).collect()
+        }
+
+        //Los únicos roles que un usuario puede pedirse a sí mismo son comprador y vendedor.
+        fn son_autoasignables(&self) -> bool {
+            self.0 & !Self::bits(&Rol::Ambos) == 0
+        }
+    }
+
+    impl From<Rol> for Roles {
+        fn from(rol: Rol) -> Self {
+            Roles(Self::bits(&rol))
+        }
+    }
+
 
     /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
     /// module and test functions are marked with a `#[test]` attribute.
@@ -228,21 +2637,30 @@ mod usuarios_sistema {
     mod tests {
         /// Imports all the definitions from the outer scope so we can use them here.
         use super::*;
+        use ink::prelude::format;
+
+        //Aprueba la solicitud de vendedor de `cuenta` como admin y vuelve a dejar el caller que estaba.
+        fn aprobar_vendedor(sistema: &mut Sistema, cuenta: AccountId) {
+            let caller = ink::env::caller::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(sistema.admin);
+            sistema.aprobar_vendedor(cuenta).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(caller);
+        }
 
         /// We test if the default constructor does its job.
         #[ink::test]
         fn default_works() {
             let sistema = Sistema::default();
-            assert_eq!(sistema.get(), false);
+            assert!(!sistema.get());
         }
 
         /// We test a simple use case of our contract.
         #[ink::test]
         fn it_works() {
             let mut sistema = Sistema::new(false);
-            assert_eq!(sistema.get(), false);
+            assert!(!sistema.get());
             sistema.flip();
-            assert_eq!(sistema.get(), true);
+            assert!(sistema.get());
         }
 
         /// We test that we can register a user.
@@ -264,7 +2682,7 @@ mod usuarios_sistema {
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(alice);
 
             let mut sistema = Sistema::new(true);
-            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Comprador);
+            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Comprador).unwrap();
 
             assert!(sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Comprador).is_err());
         }
@@ -282,7 +2700,7 @@ mod usuarios_sistema {
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(alice);
 
             let mut sistema = Sistema::new(true);
-            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Comprador);
+            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Comprador).unwrap();
 
             assert!(sistema._existe_usuario(alice).is_ok());
 
@@ -297,14 +2715,18 @@ mod usuarios_sistema {
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(alice);
 
             let mut sistema = Sistema::new(true);
-            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Vendedor);
+            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Vendedor).unwrap();
+
+            //Hasta que no se aprueba su solicitud no opera como vendedora.
+            assert!(matches!(sistema.es_vendedor(), Ok(false)));
+            aprobar_vendedor(&mut sistema, alice);
 
             //Pruebo con un usuario (alice) que esté en el sistema y sea vendedor.
             assert!(matches!(sistema.es_vendedor(), Ok(true)));
 
             let charlie = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>().charlie;
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(charlie);
-            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Comprador);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Comprador).unwrap();
 
             //Pruebo con un usuario (charlie) que esté en el sistema pero no sea vendedor.
             assert!(matches!(sistema.es_vendedor(), Ok(false)));
@@ -323,14 +2745,14 @@ mod usuarios_sistema {
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(alice);
 
             let mut sistema = Sistema::new(true);
-            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Comprador);
+            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Comprador).unwrap();
 
             //Pruebo con un usuario (alice) que esté en el sistema y sea comprador.
             assert!(matches!(sistema.es_comprador(), Ok(true)));
 
             let charlie = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>().charlie;
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(charlie);
-            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Vendedor);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Vendedor).unwrap();
 
             //Pruebo con un usuario (charlie) que esté en el sistema pero no sea vendedor.
             assert!(matches!(sistema.es_comprador(), Ok(false)));
@@ -350,12 +2772,12 @@ mod usuarios_sistema {
 
             let mut sistema = Sistema::new(true);
             //Inicializa alice como comprador.
-            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Comprador);
+            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Comprador).unwrap();
 
             //Se agrega el rol de vendedor (pasa a tener ambos).
             assert!(sistema.agregar_rol(Rol::Vendedor).is_ok());
-            if let Some(user) = sistema.usuarios.get(&alice) {
-                assert!(user.rol == Rol::Ambos);
+            if let Some(user) = sistema.usuarios.get(alice) {
+                assert!(user.roles.contiene(&Rol::Ambos));
             }
             //-----------------------------------------------------
 
@@ -364,12 +2786,12 @@ mod usuarios_sistema {
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(bob);
 
             let mut sistema = Sistema::new(true);
-            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Vendedor);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Vendedor).unwrap();
 
             //Se agrega el rol de vendedor (pasa a tener ambos).
             assert!(sistema.agregar_rol(Rol::Comprador).is_ok());
-            if let Some(user) = sistema.usuarios.get(&bob) {
-                assert!(user.rol == Rol::Ambos);
+            if let Some(user) = sistema.usuarios.get(bob) {
+                assert!(user.roles.contiene(&Rol::Ambos));
             }
 
             //-----------------------------------------------------
@@ -379,7 +2801,7 @@ mod usuarios_sistema {
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(charlie);
 
             let mut sistema = Sistema::new(true);
-            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Vendedor);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Vendedor).unwrap();
 
             //Ya tiene el rol de vendedor. Por lo qe no se puede agregar el rol de vendedor otra vez..
             let error = sistema.agregar_rol(Rol::Vendedor).unwrap_err();
@@ -393,6 +2815,968 @@ mod usuarios_sistema {
             let error = sistema.agregar_rol(Rol::Vendedor).unwrap_err();
             assert_eq!(error, ErrorSistema::UsuarioNoExiste);
         }
+
+        #[ink::test]
+        fn test_roles_y_permisos() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            let mut sistema = Sistema::new(true);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            //Registrarse como Ambos equivale a tener los roles de comprador y vendedor.
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Ambos).unwrap();
+            assert_eq!(sistema.get_roles(accounts.bob).unwrap(), Vec::from([Rol::Comprador, Rol::Vendedor]));
+            assert!(sistema.tiene_rol(accounts.bob, Rol::Ambos).unwrap());
+            assert!(sistema.tiene_permiso(accounts.bob, Permiso::Comprar).unwrap());
+            assert!(!sistema.tiene_permiso(accounts.bob, Permiso::Moderar).unwrap());
+            //Publicar además necesita la solicitud de vendedor aprobada.
+            assert!(!sistema.tiene_permiso(accounts.bob, Permiso::Publicar).unwrap());
+            assert!(matches!(sistema.es_vendedor(), Ok(false)));
+
+            //Los roles privilegiados no se los puede dar uno mismo.
+            assert_eq!(sistema.agregar_rol(Rol::Moderador).unwrap_err(), ErrorSistema::RolNoPermitido);
+            assert_eq!(sistema.asignar_rol(accounts.bob, Rol::Moderador).unwrap_err(), ErrorSistema::NoEsAdmin);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            let error = sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Admin).unwrap_err();
+            assert_eq!(error, ErrorSistema::RolNoPermitido);
+
+            //Bob (que no desplegó el contrato) pasa a administrar cuando le asignan el rol Admin.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            sistema.asignar_rol(accounts.bob, Rol::Admin).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(sistema.tiene_permiso(accounts.bob, Permiso::Moderar).unwrap());
+            sistema.crear_categoria(String::from("Libros"), None).unwrap();
+            sistema.configurar_permisos(Rol::Comprador, Vec::new()).unwrap();
+            assert!(!sistema.tiene_permiso(accounts.bob, Permiso::Comprar).unwrap());
+            //La tabla de permisos es la que decide quién puede actuar como comprador.
+            let error = sistema.agregar_direccion(ContenidoDireccion::Texto(String::from("Calle 1"))).unwrap_err();
+            assert_eq!(error, ErrorSistema::NoEsComprador);
+
+            sistema.quitar_rol(accounts.bob, Rol::Vendedor).unwrap();
+            assert_eq!(sistema.get_roles(accounts.bob).unwrap(), Vec::from([Rol::Comprador, Rol::Admin]));
+            //Quitar un rol que no tiene es un error y no queda en el historial.
+            let cambios = sistema.historial_usuario(accounts.bob, 0, 20).len();
+            assert_eq!(sistema.quitar_rol(accounts.bob, Rol::Vendedor).unwrap_err(), ErrorSistema::RolNoAsignado);
+            assert_eq!(sistema.historial_usuario(accounts.bob, 0, 20).len(), cambios);
+        }
+
+        #[ink::test]
+        fn test_historial_usuario() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let mut sistema = Sistema::new(true);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador).unwrap();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            sistema.agregar_rol(Rol::Vendedor).unwrap();
+            aprobar_vendedor(&mut sistema, accounts.bob);
+            sistema.editar_perfil_vendedor(String::from("Tienda"), String::new(), None, String::new(), String::new()).unwrap();
+
+            let historial = sistema.historial_usuario(accounts.bob, 0, 10);
+            assert_eq!(historial.len(), 4);
+            assert_eq!(historial[0].tipo, TipoCambio::Registro(Roles::from(Rol::Comprador)));
+            assert_eq!(historial[1], Cambio { tipo: TipoCambio::RolAgregado(Rol::Vendedor), autor: accounts.bob, bloque: 1 });
+            //La aprobación la hizo la admin.
+            assert_eq!(historial[2].tipo, TipoCambio::SolicitudVendedorResuelta(EstadoSolicitud::Aprobada));
+            assert_eq!(historial[2].autor, accounts.alice);
+            assert_eq!(historial[3].tipo, TipoCambio::PerfilVendedorEditado);
+
+            assert_eq!(sistema.historial_usuario(accounts.bob, 1, 3).len(), 1);
+            assert!(sistema.historial_usuario(accounts.charlie, 0, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn test_actividad() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let mut sistema = Sistema::new(true);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Comprador).unwrap();
+            assert_eq!(sistema.get_actividad(accounts.bob).unwrap(), (FechaBloque { timestamp: 1_000, bloque: 0 }, 1_000));
+
+            //Charlie usa el sistema más tarde; Bob no.
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(5_000);
+            sistema.agregar_direccion(ContenidoDireccion::Texto(String::from("Calle Falsa 123"))).unwrap();
+            assert_eq!(sistema.get_actividad(accounts.charlie).unwrap().1, 5_000);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(6_000);
+            assert_eq!(sistema.listar_inactivos(3_000, 0, 10).unwrap_err(), ErrorSistema::NoEsAdmin);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(sistema.listar_inactivos(3_000, 0, 10).unwrap(), Vec::from([accounts.bob]));
+            assert_eq!(sistema.listar_inactivos(500, 0, 10).unwrap().len(), 2);
+            assert_eq!(sistema.listar_inactivos(500, 1, 1).unwrap(), Vec::from([accounts.charlie]));
+            //La página recorre las cuentas registradas, así que puede venir incompleta.
+            assert!(sistema.listar_inactivos(3_000, 1, 1).unwrap().is_empty());
+        }
+
+        #[ink::test]
+        fn test_limpiar_inactivos() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let mut sistema = Sistema::new(true);
+            let libros = sistema.crear_categoria(String::from("Libros"), None).unwrap();
+            sistema.configurar_tarifa_registro(100).unwrap();
+            sistema.configurar_limpieza(Some(1_000), 30).unwrap();
+            let contrato = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(contrato, 1_000_000);
+
+            //Bob es un comprador y Charlie un vendedor con una publicación.
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Vendedor).unwrap();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            aprobar_vendedor(&mut sistema, accounts.charlie);
+            sistema.crear_publicacion(String::from("Libro"), String::from("Usado"), 100, 1, libros).unwrap();
+
+            //Todavía no pasó el período de inactividad.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(sistema.limpiar_inactivos(Vec::from([accounts.bob, accounts.charlie])).unwrap(), 0);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2_000);
+            let bob_antes = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob).unwrap();
+            let django_antes = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.django).unwrap();
+            let eventos_antes = ink::env::test::recorded_events().count();
+            //Charlie tiene publicaciones, así que solo se elimina a Bob.
+            assert_eq!(sistema.limpiar_inactivos(Vec::from([accounts.bob, accounts.charlie, accounts.eve])).unwrap(), 1);
+            assert!(sistema._existe_usuario(accounts.bob).is_err());
+            assert!(sistema._existe_usuario(accounts.charlie).is_ok());
+
+            //Django se lleva la recompensa y a Bob se le devuelve el resto de su depósito.
+            let bob_despues = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob).unwrap();
+            let django_despues = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.django).unwrap();
+            assert_eq!(bob_despues - bob_antes, 70);
+            assert_eq!(django_despues - django_antes, 30);
+            assert_eq!(ink::env::test::recorded_events().count(), eventos_antes + 1);
+
+            //Un pago que no se pudo hacer queda como saldo para retirar.
+            assert_eq!(sistema.retirar_saldo().unwrap_err(), ErrorSistema::FondosInsuficientes);
+            sistema.saldos.insert(accounts.django, &30);
+            assert_eq!(sistema.retirar_saldo().unwrap(), 30);
+            assert_eq!(sistema.get_saldo(accounts.django), 0);
+        }
+
+        #[ink::test]
+        fn test_migrar_cuenta() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let mut sistema = Sistema::new(true);
+            let libros = sistema.crear_categoria(String::from("Libros"), None).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Ambos).unwrap();
+            aprobar_vendedor(&mut sistema, accounts.bob);
+            let producto = sistema.crear_publicacion(String::from("Libro"), String::from("Usado"), 100, 1, libros).unwrap();
+            sistema.agregar_direccion(ContenidoDireccion::Texto(String::from("Calle Falsa 123"))).unwrap();
+
+            //No se puede migrar a una cuenta que ya está registrada.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Comprador).unwrap();
+            sistema.reportar_usuario(accounts.bob, MotivoReporte::Spam).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(sistema.migrar_cuenta(accounts.charlie).unwrap_err(), ErrorSistema::CuentaEnUso);
+
+            //Bob reporta a Charlie, emite una invitación y es guardián de Charlie, con un pedido de recuperación aprobado.
+            sistema.reportar_usuario(accounts.charlie, MotivoReporte::Spam).unwrap();
+            let invitacion = sistema.hash_codigo_invitacion(String::from("bienvenido"), accounts.frank);
+            sistema.crear_invitacion(invitacion).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.configurar_guardianes(Vec::from([accounts.bob, accounts.django]), 1).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.iniciar_recuperacion(accounts.charlie, accounts.frank).unwrap();
+
+            sistema.migrar_cuenta(accounts.eve).unwrap();
+            //Charlie no puede pisar el pedido de Bob.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(sistema.migrar_cuenta(accounts.eve).unwrap_err(), ErrorSistema::CuentaEnUso);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            //Hasta que Eve no acepta no cambia nada; y solo Eve puede aceptar.
+            assert!(sistema._existe_usuario(accounts.bob).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(sistema.aceptar_migracion().unwrap_err(), ErrorSistema::MigracionNoExiste);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            sistema.aceptar_migracion().unwrap();
+            assert!(sistema._existe_usuario(accounts.bob).is_err());
+            assert_eq!(sistema._get_user(accounts.eve).unwrap().id, accounts.eve);
+            assert!(matches!(sistema.es_vendedor(), Ok(true)));
+            assert!(sistema.get_perfil_vendedor(accounts.eve).is_ok());
+            assert_eq!(sistema.get_publicacion(producto).unwrap().vendedor, accounts.eve);
+            assert_eq!(sistema.listar_direcciones().len(), 1);
+            //Lo que otros usuarios tenían a nombre de Bob también pasa a Eve.
+            assert_eq!(sistema.get_guardianes(accounts.charlie).unwrap().cuentas, [accounts.eve, accounts.django]);
+            assert_eq!(sistema.get_recuperacion(accounts.charlie).unwrap().aprobaciones, [accounts.eve]);
+            assert_eq!(sistema.reportar_usuario(accounts.charlie, MotivoReporte::Spam).unwrap_err(), ErrorSistema::YaReportado);
+            assert_eq!(sistema.invitaciones.get(invitacion).unwrap().0, accounts.eve);
+            let historial = sistema.historial_usuario(accounts.eve, 0, 10);
+            assert_eq!(historial.last().unwrap().tipo, TipoCambio::CuentaMigrada(accounts.bob));
+            assert!(sistema.historial_usuario(accounts.bob, 0, 10).is_empty());
+            //El reporte abierto contra Bob pasa a apuntar a la cuenta nueva.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(sistema.listar_reportes_pendientes(0, 10).unwrap()[0].objeto, ObjetoReporte::Usuario(accounts.eve));
+
+            //Django se registró y se dio de baja: al migrarse Eve a su cuenta, el historial viejo de Django se conserva.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            sistema.registrar_usuario(String::from("Django"), String::from("Surname"), String::from("django.email"), Rol::Comprador).unwrap();
+            sistema.darse_de_baja().unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            sistema.migrar_cuenta(accounts.django).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            sistema.aceptar_migracion().unwrap();
+            let historial_django = sistema.historial_usuario(accounts.django, 0, 20);
+            assert_eq!(historial_django.len(), historial.len() + 2);
+            assert_eq!(historial_django[0].tipo, TipoCambio::Registro(Roles::from(Rol::Comprador)));
+            assert_eq!(historial_django[1..=historial.len()], historial[..]);
+            assert_eq!(historial_django.last().unwrap().tipo, TipoCambio::CuentaMigrada(accounts.eve));
+
+            //Alice (admin, sin registrarse) emitió una invitación. Si Django se migra a su cuenta, las invitaciones se suman.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let del_admin = sistema.hash_codigo_invitacion(String::from("del-admin"), accounts.frank);
+            sistema.crear_invitacion(del_admin).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            sistema.migrar_cuenta(accounts.alice).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            sistema.aceptar_migracion().unwrap();
+            let mut emitidas = sistema._pagina_de_ids(Lista::InvitacionesDe(accounts.alice), 0, 10);
+            emitidas.sort();
+            assert_eq!(emitidas, [0, 1]);
+            assert_eq!(sistema.invitaciones.get(del_admin).unwrap(), (accounts.alice, 1));
+            assert_eq!(sistema.invitaciones.get(invitacion).unwrap(), (accounts.alice, 0));
+        }
+
+        #[ink::test]
+        fn test_recuperacion_social() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let mut sistema = Sistema::new(true);
+            sistema.configurar_recuperacion(100, 50).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador).unwrap();
+            let error = sistema.configurar_guardianes(Vec::from([accounts.charlie]), 2).unwrap_err();
+            assert_eq!(error, ErrorSistema::DatosInvalidos);
+            let demasiados: Vec<AccountId> = (0..=10).map(|i| AccountId::from([i; 32])).collect();
+            assert_eq!(sistema.configurar_guardianes(demasiados, 1).unwrap_err(), ErrorSistema::DatosInvalidos);
+            sistema.configurar_guardianes(Vec::from([accounts.charlie, accounts.django, accounts.alice]), 2).unwrap();
+
+            //Eve no es guardiana de Bob.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            assert_eq!(sistema.iniciar_recuperacion(accounts.bob, accounts.eve).unwrap_err(), ErrorSistema::NoEsGuardian);
+
+            //Un pedido que no junta las aprobaciones a tiempo vence.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.iniciar_recuperacion(accounts.bob, accounts.eve).unwrap();
+            assert_eq!(sistema.ejecutar_recuperacion(accounts.bob).unwrap_err(), ErrorSistema::UmbralNoAlcanzado);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(101);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(sistema.aprobar_recuperacion(accounts.bob).unwrap_err(), ErrorSistema::RecuperacionVencida);
+
+            //Bob cancela una recuperación ya aprobada.
+            sistema.iniciar_recuperacion(accounts.bob, accounts.eve).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.aprobar_recuperacion(accounts.bob).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.cancelar_recuperacion().unwrap();
+            assert!(sistema.get_recuperacion(accounts.bob).is_none());
+
+            //Se vuelve a pedir y Bob no la cancela.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.iniciar_recuperacion(accounts.bob, accounts.eve).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            sistema.aprobar_recuperacion(accounts.bob).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(150);
+            assert_eq!(sistema.ejecutar_recuperacion(accounts.bob).unwrap_err(), ErrorSistema::PlazoNoCumplido);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(151);
+            sistema.ejecutar_recuperacion(accounts.bob).unwrap();
+
+            assert!(sistema._existe_usuario(accounts.bob).is_err());
+            assert_eq!(sistema._get_user(accounts.eve).unwrap().nombre, String::from("Bob"));
+            assert!(sistema.get_guardianes(accounts.eve).is_some());
+        }
+
+        #[ink::test]
+        fn test_handles() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let mut sistema = Sistema::new(true);
+            sistema.reservar_handle(String::from("admin")).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador).unwrap();
+            assert_eq!(sistema.reclamar_handle(String::from("Bob")).unwrap_err(), ErrorSistema::HandleInvalido);
+            assert_eq!(sistema.reclamar_handle(String::from("bo")).unwrap_err(), ErrorSistema::HandleInvalido);
+            assert_eq!(sistema.reclamar_handle(String::from("admin")).unwrap_err(), ErrorSistema::HandleReservado);
+            sistema.reclamar_handle(String::from("bob_42")).unwrap();
+            assert_eq!(sistema.resolver_handle(String::from("bob_42")), Some(accounts.bob));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Comprador).unwrap();
+            assert_eq!(sistema.reclamar_handle(String::from("bob_42")).unwrap_err(), ErrorSistema::HandleEnUso);
+
+            //Bob le pasa su handle a Charlie.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.transferir_handle(accounts.charlie).unwrap();
+            assert_eq!(sistema.resolver_handle(String::from("bob_42")), Some(accounts.charlie));
+            //La transferencia queda en el historial de las dos cuentas.
+            let historial_bob = sistema.historial_usuario(accounts.bob, 0, 10);
+            assert_eq!(historial_bob[historial_bob.len() - 2].tipo, TipoCambio::HandleCambiado(Some(String::from("bob_42"))));
+            assert_eq!(historial_bob.last().unwrap().tipo, TipoCambio::HandleCambiado(None));
+            let historial_charlie = sistema.historial_usuario(accounts.charlie, 0, 10);
+            assert_eq!(historial_charlie.last().unwrap().tipo, TipoCambio::HandleCambiado(Some(String::from("bob_42"))));
+            assert_eq!(sistema.liberar_handle().unwrap_err(), ErrorSistema::SinHandle);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.liberar_handle().unwrap();
+            assert_eq!(sistema.resolver_handle(String::from("bob_42")), None);
+        }
+
+        #[ink::test]
+        fn test_favoritos() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let mut sistema = Sistema::new(true);
+            let libros = sistema.crear_categoria(String::from("Libros"), None).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Vendedor).unwrap();
+            aprobar_vendedor(&mut sistema, accounts.bob);
+            let primero = sistema.crear_publicacion(String::from("Libro 1"), String::from("Usado"), 100, 1, libros).unwrap();
+            let segundo = sistema.crear_publicacion(String::from("Libro 2"), String::from("Usado"), 100, 1, libros).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Comprador).unwrap();
+            sistema.agregar_favorito(primero).unwrap();
+            sistema.agregar_favorito(segundo).unwrap();
+            assert_eq!(sistema.agregar_favorito(primero).unwrap_err(), ErrorSistema::YaEsFavorito);
+            assert_eq!(sistema.listar_favoritos(0, 1)[0].id, primero);
+            assert_eq!(sistema.listar_favoritos(0, 10).len(), 2);
+            //Sólo el vendedor ve cuántos lo guardaron.
+            assert_eq!(sistema.cantidad_favoritos(primero).unwrap_err(), ErrorSistema::NoEsVendedor);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            sistema.registrar_usuario(String::from("Django"), String::from("Surname"), String::from("django.email"), Rol::Comprador).unwrap();
+            sistema.agregar_favorito(primero).unwrap();
+            sistema.quitar_favorito(primero).unwrap();
+            assert_eq!(sistema.quitar_favorito(primero).unwrap_err(), ErrorSistema::NoEsFavorito);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(sistema.cantidad_favoritos(primero).unwrap(), 1);
+            assert_eq!(sistema.agregar_favorito(primero).unwrap_err(), ErrorSistema::NoEsComprador);
+
+            //Al retirar la publicación desaparece de los favoritos.
+            sistema.retirar_publicacion(primero).unwrap();
+            assert!(sistema.listar_por_categoria(libros, 0, 10).unwrap().iter().all(|publicacion| publicacion.id != primero));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            let favoritos = sistema.listar_favoritos(0, 10);
+            assert_eq!(favoritos.len(), 1);
+            assert_eq!(favoritos[0].id, segundo);
+            //El producto retirado se borra de la lista guardada la próxima vez que Charlie la modifica.
+            sistema.quitar_favorito(segundo).unwrap();
+            assert!(sistema.favoritos.get(accounts.charlie).unwrap().is_empty());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(sistema.cantidad_favoritos(segundo).unwrap(), 0);
+        }
+
+        #[ink::test]
+        fn test_seguir_vendedores() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let mut sistema = Sistema::new(true);
+            let libros = sistema.crear_categoria(String::from("Libros"), None).unwrap();
+
+            for (cuenta, nombre) in [(accounts.bob, "Bob"), (accounts.eve, "Eve")] {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(cuenta);
+                sistema.registrar_usuario(String::from(nombre), String::from("Surname"), format!("{}.email", nombre), Rol::Vendedor).unwrap();
+                aprobar_vendedor(&mut sistema, cuenta);
+            }
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Comprador).unwrap();
+            sistema.seguir_vendedor(accounts.bob).unwrap();
+            sistema.seguir_vendedor(accounts.eve).unwrap();
+            assert_eq!(sistema.seguir_vendedor(accounts.bob).unwrap_err(), ErrorSistema::YaSigue);
+            assert_eq!(sistema.seguir_vendedor(accounts.charlie).unwrap_err(), ErrorSistema::DatosInvalidos);
+            assert_eq!(sistema.listar_seguidos(accounts.charlie, 0, 1), [accounts.bob]);
+            assert_eq!(sistema.listar_seguidos(accounts.charlie, 1, 1), [accounts.eve]);
+            assert_eq!(sistema.cantidad_seguidores(accounts.bob), 1);
+
+            sistema.dejar_de_seguir(accounts.bob).unwrap();
+            assert_eq!(sistema.dejar_de_seguir(accounts.bob).unwrap_err(), ErrorSistema::NoSigue);
+            assert_eq!(sistema.cantidad_seguidores(accounts.bob), 0);
+            assert_eq!(sistema.listar_seguidos(accounts.charlie, 0, 10), [accounts.eve]);
+
+            //Crear una publicación emite NuevaPublicacion.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            let eventos_antes = ink::env::test::recorded_events().count();
+            sistema.crear_publicacion(String::from("Libro"), String::from("Usado"), 100, 1, libros).unwrap();
+            assert_eq!(ink::env::test::recorded_events().count(), eventos_antes + 1);
+
+            //Si Eve migra su cuenta, Charlie la sigue en la cuenta nueva.
+            sistema.migrar_cuenta(accounts.django).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            sistema.aceptar_migracion().unwrap();
+            assert_eq!(sistema.cantidad_seguidores(accounts.django), 1);
+            assert_eq!(sistema.cantidad_seguidores(accounts.eve), 0);
+            assert_eq!(sistema.listar_seguidos(accounts.charlie, 0, 10), [accounts.django]);
+        }
+
+        #[ink::test]
+        fn test_reportes_y_moderacion() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let mut sistema = Sistema::new(true);
+            let libros = sistema.crear_categoria(String::from("Libros"), None).unwrap();
+            sistema.configurar_umbral_reportes(2).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Vendedor).unwrap();
+            aprobar_vendedor(&mut sistema, accounts.bob);
+            let estafa = sistema.crear_publicacion(String::from("Libro"), String::from("Usado"), 100, 1, libros).unwrap();
+            let otra = sistema.crear_publicacion(String::from("Libro 2"), String::from("Usado"), 100, 1, libros).unwrap();
+
+            for (cuenta, nombre) in [(accounts.charlie, "Charlie"), (accounts.django, "Django")] {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(cuenta);
+                sistema.registrar_usuario(String::from(nombre), String::from("Surname"), format!("{}.email", nombre), Rol::Comprador).unwrap();
+            }
+
+            //Un mismo usuario reporta una sola vez; al segundo reportante se oculta sola.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.reportar_publicacion(estafa, MotivoReporte::Estafa).unwrap();
+            assert_eq!(sistema.reportar_publicacion(estafa, MotivoReporte::Spam).unwrap_err(), ErrorSistema::YaReportado);
+            assert_eq!(sistema.listar_por_categoria(libros, 0, 10).unwrap().len(), 2);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            sistema.reportar_publicacion(estafa, MotivoReporte::Estafa).unwrap();
+            let reporte_usuario = sistema.reportar_usuario(accounts.bob, MotivoReporte::Estafa).unwrap();
+            assert_eq!(sistema.listar_por_categoria(libros, 0, 10).unwrap()[0].id, otra);
+            //Las páginas se arman antes de descartar las ocultas, así que pueden venir cortas.
+            assert!(sistema.listar_por_categoria(libros, 0, 1).unwrap().is_empty());
+            assert_eq!(sistema.listar_reportes_pendientes(0, 10).unwrap_err(), ErrorSistema::NoEsModerador);
+
+            //El moderador ve la cola y actúa.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(sistema.listar_reportes_pendientes(0, 10).unwrap().len(), 3);
+            sistema.restaurar_publicacion(estafa).unwrap();
+            assert_eq!(sistema.listar_por_categoria(libros, 0, 10).unwrap().len(), 2);
+            //Al restaurarla los reportes anteriores dejan de contar, pero no se pueden repetir.
+            assert_eq!(sistema.contador_reportes.get(ObjetoReporte::Publicacion(estafa)), None);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(sistema.reportar_publicacion(estafa, MotivoReporte::Spam).unwrap_err(), ErrorSistema::YaReportado);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(sistema.advertir_usuario(accounts.bob, MotivoReporte::Estafa).unwrap(), 1);
+            sistema.suspender_vendedor(accounts.bob).unwrap();
+            sistema.cerrar_reporte(reporte_usuario).unwrap();
+            assert_eq!(sistema.cerrar_reporte(reporte_usuario).unwrap_err(), ErrorSistema::ReporteNoExiste);
+            let pendientes = sistema.listar_reportes_pendientes(0, 10).unwrap();
+            assert_eq!(pendientes.len(), 2);
+            assert!(pendientes.iter().all(|reporte| reporte.objeto == ObjetoReporte::Publicacion(estafa)));
+
+            //Suspendido: no publica y no se listan sus publicaciones.
+            assert!(sistema.listar_por_categoria(libros, 0, 10).unwrap().is_empty());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let error = sistema.crear_publicacion(String::from("X"), String::from("X"), 1, 1, libros).unwrap_err();
+            assert_eq!(error, ErrorSistema::VendedorSuspendido);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            sistema.reactivar_vendedor(accounts.bob).unwrap();
+            assert_eq!(sistema.listar_por_categoria(libros, 0, 10).unwrap().len(), 2);
+        }
+
+        #[ink::test]
+        fn test_promociones() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let mut sistema = Sistema::new(true);
+            let libros = sistema.crear_categoria(String::from("Libros"), None).unwrap();
+            sistema.configurar_promociones(10, 1_000).unwrap();
+            let contrato = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(contrato, 1_000_000);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Vendedor).unwrap();
+            aprobar_vendedor(&mut sistema, accounts.bob);
+            let corta = sistema.crear_publicacion(String::from("Libro 1"), String::from("Usado"), 100, 1, libros).unwrap();
+            let barata = sistema.crear_publicacion(String::from("Libro 2"), String::from("Usado"), 100, 1, libros).unwrap();
+            let cara = sistema.crear_publicacion(String::from("Libro 3"), String::from("Usado"), 100, 1, libros).unwrap();
+
+            //Una publicación oculta no se puede promocionar.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            sistema.ocultar_publicacion(cara).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(90);
+            assert_eq!(sistema.promocionar_publicacion(cara, 3).unwrap_err(), ErrorSistema::PublicacionOculta);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            sistema.restaurar_publicacion(cara).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(19);
+            assert_eq!(sistema.promocionar_publicacion(corta, 2).unwrap_err(), ErrorSistema::PagoInsuficiente);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(20);
+            assert_eq!(sistema.promocionar_publicacion(corta, 1).unwrap(), 1_000);
+            assert_eq!(sistema.promocionar_publicacion(corta, 1).unwrap_err(), ErrorSistema::PromocionActiva);
+            sistema.promocionar_publicacion(barata, 2).unwrap();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(90);
+            sistema.promocionar_publicacion(cara, 3).unwrap();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            assert_eq!(sistema.get_tesoreria(), 130);
+
+            //Ordenados por oferta por unidad: 30, 20 y 10.
+            let destacados: Vec<u32> = sistema.listar_destacados(0, 10).iter().map(|publicacion| publicacion.id).collect();
+            assert_eq!(destacados, [cara, corta, barata]);
+
+            //La promoción de una unidad vence primero.
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            let destacados: Vec<u32> = sistema.listar_destacados(0, 10).iter().map(|publicacion| publicacion.id).collect();
+            assert_eq!(destacados, [cara, barata]);
+            assert!(sistema.listar_destacados(1, 2).is_empty());
+
+            //La duración tiene un máximo, y con precio 0 las promociones están deshabilitadas.
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(10 * 721);
+            assert_eq!(sistema.promocionar_publicacion(corta, 721).unwrap_err(), ErrorSistema::DatosInvalidos);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            sistema.configurar_promociones(0, 1_000).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(sistema.promocionar_publicacion(corta, 1).unwrap_err(), ErrorSistema::PromocionesDeshabilitadas);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(sistema.retirar_tesoreria(accounts.alice, 131).unwrap_err(), ErrorSistema::FondosInsuficientes);
+            sistema.retirar_tesoreria(accounts.alice, 130).unwrap();
+            assert_eq!(sistema.get_tesoreria(), 0);
+        }
+
+        #[ink::test]
+        fn test_perfil_vendedor() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            let mut sistema = Sistema::new(true);
+            //Alice se registra como vendedora. Al aprobarse su solicitud se le crea un perfil.
+            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Vendedor).unwrap();
+            assert_eq!(sistema.get_perfil_vendedor(accounts.alice).unwrap_err(), ErrorSistema::PerfilNoExiste);
+            aprobar_vendedor(&mut sistema, accounts.alice);
+            assert_eq!(sistema.get_perfil_vendedor(accounts.alice).unwrap(), PerfilVendedor::default());
+
+            let logo = Hash::from([7; 32]);
+            sistema.editar_perfil_vendedor(String::from("Tienda de Alice"), String::from("Libros usados"), Some(logo), String::from("alice.email"), String::from("20-12345678-9")).unwrap();
+            let perfil = sistema.get_perfil_vendedor(accounts.alice).unwrap();
+            assert_eq!(perfil.nombre_tienda, String::from("Tienda de Alice"));
+            assert_eq!(perfil.logo, Some(logo));
+
+            //Bob es comprador: no tiene perfil hasta que agrega el rol de vendedor.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador).unwrap();
+            assert_eq!(sistema.get_perfil_vendedor(accounts.bob).unwrap_err(), ErrorSistema::PerfilNoExiste);
+            let error = sistema.editar_perfil_vendedor(String::from("X"), String::new(), None, String::new(), String::new()).unwrap_err();
+            assert_eq!(error, ErrorSistema::NoEsVendedor);
+
+            sistema.agregar_rol(Rol::Vendedor).unwrap();
+            aprobar_vendedor(&mut sistema, accounts.bob);
+            assert!(sistema.get_perfil_vendedor(accounts.bob).is_ok());
+        }
+
+        #[ink::test]
+        fn test_direcciones() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            let mut sistema = Sistema::new(true);
+            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Comprador).unwrap();
+
+            let casa = sistema.agregar_direccion(ContenidoDireccion::Texto(String::from("Calle Falsa 123"))).unwrap();
+            let trabajo = sistema.agregar_direccion(ContenidoDireccion::Hash(Hash::from([1; 32]))).unwrap();
+            assert_eq!(sistema.listar_direcciones().len(), 2);
+
+            //La primera dirección queda como predeterminada.
+            assert_eq!(sistema.get_direccion_predeterminada().unwrap().id, casa);
+            sistema.elegir_direccion_predeterminada(trabajo).unwrap();
+            assert_eq!(sistema.get_direccion_predeterminada().unwrap().id, trabajo);
+
+            //Al eliminar la predeterminada pasa a serlo la que queda.
+            sistema.eliminar_direccion(trabajo).unwrap();
+            assert_eq!(sistema.get_direccion_predeterminada().unwrap().id, casa);
+            assert_eq!(sistema.elegir_direccion_predeterminada(trabajo).unwrap_err(), ErrorSistema::DireccionNoExiste);
+
+            //Bob no ve las direcciones de Alice y, como es vendedor, no puede cargar direcciones.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Vendedor).unwrap();
+            assert!(sistema.listar_direcciones().is_empty());
+            let error = sistema.agregar_direccion(ContenidoDireccion::Cifrada(Vec::from([1, 2, 3]))).unwrap_err();
+            assert_eq!(error, ErrorSistema::NoEsComprador);
+        }
+
+        #[ink::test]
+        fn test_registrar_usuario_firmado() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            //Bob es el relayer que paga la transacción.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let mut sistema = Sistema::new(true);
+
+            let par = ink_e2e::alice();
+            let firmante = AccountId::from(par.public_key().0);
+            let solicitud = |nonce: u64, vencimiento: Timestamp| SolicitudRegistro {
+                nombre: String::from("Alice"),
+                apellido: String::from("Surname"),
+                email: String::from("alice.email"),
+                rol: Rol::Comprador,
+                nonce,
+                vencimiento,
+                invitacion: None,
+            };
+            let firmar = |sistema: &Sistema, solicitud: SolicitudRegistro| {
+                let mensaje = sistema.mensaje_registro(solicitud);
+                FirmaRegistro::Sr25519 { firmante, firma: par.sign(&mensaje).0 }
+            };
+
+            //Una firma sobre otros datos no es válida.
+            let firma = firmar(&sistema, solicitud(0, 100));
+            let mut adulterada = solicitud(0, 100);
+            adulterada.nombre = String::from("Mallory");
+            let error = sistema.registrar_usuario_firmado(adulterada, firma.clone()).unwrap_err();
+            assert_eq!(error, ErrorSistema::FirmaInvalida);
+
+            let registrado = sistema.registrar_usuario_firmado(solicitud(0, 100), firma.clone()).unwrap();
+            assert_eq!(registrado, firmante);
+            assert!(sistema._existe_usuario(firmante).is_ok());
+            assert!(sistema._existe_usuario(accounts.bob).is_err());
+            assert_eq!(sistema.get_nonce(firmante), 1);
+
+            //La misma firma no se puede reutilizar.
+            let error = sistema.registrar_usuario_firmado(solicitud(0, 100), firma).unwrap_err();
+            assert_eq!(error, ErrorSistema::NonceInvalido);
+
+            //Una firma vencida tampoco.
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(200);
+            let firma = firmar(&sistema, solicitud(1, 100));
+            let error = sistema.registrar_usuario_firmado(solicitud(1, 100), firma).unwrap_err();
+            assert_eq!(error, ErrorSistema::FirmaVencida);
+
+            //En modo SoloInvitacion el código va dentro de lo firmado. Bob es el admin y emite la invitación.
+            sistema.configurar_modo_registro(ModoRegistro::SoloInvitacion).unwrap();
+            let otro = ink_e2e::bob();
+            let otro_firmante = AccountId::from(otro.public_key().0);
+            sistema.crear_invitacion(sistema.hash_codigo_invitacion(String::from("codigo"), otro_firmante)).unwrap();
+            let firmar_otro = |sistema: &Sistema, solicitud: SolicitudRegistro| {
+                let mensaje = sistema.mensaje_registro(solicitud);
+                FirmaRegistro::Sr25519 { firmante: otro_firmante, firma: otro.sign(&mensaje).0 }
+            };
+            let firma = firmar_otro(&sistema, solicitud(0, 300));
+            let error = sistema.registrar_usuario_firmado(solicitud(0, 300), firma).unwrap_err();
+            assert_eq!(error, ErrorSistema::InvitacionRequerida);
+            let mut con_invitacion = solicitud(0, 300);
+            con_invitacion.invitacion = Some(String::from("codigo"));
+            let firma = firmar_otro(&sistema, con_invitacion.clone());
+            assert_eq!(sistema.registrar_usuario_firmado(con_invitacion, firma).unwrap(), otro_firmante);
+        }
+
+        #[ink::test]
+        fn test_registrar_usuario_firmado_ecdsa() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let mut sistema = Sistema::new(true);
+
+            //La cuenta esperada se calcula con subxt, no con el contrato.
+            let par = ink_e2e::subxt_signer::ecdsa::dev::alice();
+            let firmante = AccountId::from(par.public_key().to_account_id().0);
+            let solicitud = SolicitudRegistro {
+                nombre: String::from("Alice"),
+                apellido: String::from("Surname"),
+                email: String::from("alice.email"),
+                rol: Rol::Comprador,
+                nonce: 0,
+                vencimiento: 100,
+                invitacion: None,
+            };
+            let firma = FirmaRegistro::Ecdsa { firmante, firma: par.sign(&sistema.mensaje_registro(solicitud.clone())).0 };
+
+            //Con otros datos la firma recupera otra clave, que no es la del firmante.
+            let mut adulterada = solicitud.clone();
+            adulterada.nombre = String::from("Mallory");
+            let error = sistema.registrar_usuario_firmado(adulterada, firma.clone()).unwrap_err();
+            assert_eq!(error, ErrorSistema::FirmaInvalida);
+            assert!(sistema._existe_usuario(firmante).is_err());
+
+            assert_eq!(sistema.registrar_usuario_firmado(solicitud, firma).unwrap(), firmante);
+            assert_eq!(sistema._get_user(firmante).unwrap().nombre, String::from("Alice"));
+            assert_eq!(sistema.get_nonce(firmante), 1);
+        }
+
+        #[ink::test]
+        fn test_verificacion() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            //Alice es la admin y nombra a Charlie como verificador.
+            let mut sistema = Sistema::new(true);
+            let libros = sistema.crear_categoria(String::from("Libros"), None).unwrap();
+            sistema.agregar_verificador(accounts.charlie).unwrap();
+            sistema.configurar_umbral_verificacion(Some(ReglaVerificacion { valor_maximo: 1000, nivel_minimo: 2 })).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Vendedor).unwrap();
+            aprobar_vendedor(&mut sistema, accounts.bob);
+            //Por debajo del umbral no hace falta estar verificado.
+            let libro = sistema.crear_publicacion(String::from("Libro"), String::from("Usado"), 100, 10, libros).unwrap();
+            let error = sistema.crear_publicacion(String::from("Colección"), String::from("Usada"), 100, 11, libros).unwrap_err();
+            assert_eq!(error, ErrorSistema::VerificacionRequerida);
+            //Cuenta el total publicado: partir la colección en publicaciones chicas no alcanza.
+            let error = sistema.crear_publicacion(String::from("Tomo 1"), String::from("Usado"), 100, 1, libros).unwrap_err();
+            assert_eq!(error, ErrorSistema::VerificacionRequerida);
+            //Lo retirado deja de contar.
+            sistema.retirar_publicacion(libro).unwrap();
+            assert_eq!(sistema.valor_publicado.get(accounts.bob), Some(0));
+            sistema.crear_publicacion(String::from("Tomo 1"), String::from("Usado"), 100, 1, libros).unwrap();
+
+            //Bob no es verificador.
+            let error = sistema.verificar_usuario(accounts.bob, 2, 100, Hash::from([1; 32])).unwrap_err();
+            assert_eq!(error, ErrorSistema::NoEsVerificador);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.verificar_usuario(accounts.bob, 2, 100, Hash::from([1; 32])).unwrap();
+            assert_eq!(sistema.nivel_verificacion(accounts.bob).unwrap(), 2);
+            assert_eq!(sistema.get_verificacion(accounts.bob).unwrap().unwrap().verificador, accounts.charlie);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(sistema.crear_publicacion(String::from("Colección"), String::from("Usada"), 100, 11, libros).is_ok());
+
+            //Una vez vencida la verificación vuelve a hacer falta.
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(101);
+            assert_eq!(sistema.nivel_verificacion(accounts.bob).unwrap(), 0);
+            let error = sistema.crear_publicacion(String::from("Colección"), String::from("Usada"), 100, 11, libros).unwrap_err();
+            assert_eq!(error, ErrorSistema::VerificacionRequerida);
+        }
+
+        #[ink::test]
+        fn test_tarifa_registro() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            let mut sistema = Sistema::new(true);
+            sistema.configurar_tarifa_registro(50).unwrap();
+
+            //Bob paga menos que la tarifa.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(49);
+            let error = sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador).unwrap_err();
+            assert_eq!(error, ErrorSistema::PagoInsuficiente);
+
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(50);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador).unwrap();
+            assert_eq!(sistema._get_user(accounts.bob).unwrap().deposito, 50);
+
+            //Al darse de baja se le devuelve el depósito.
+            let contrato = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(contrato, 1_000_000);
+            let antes = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob).unwrap();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            sistema.darse_de_baja().unwrap();
+            let despues = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob).unwrap();
+            assert_eq!(despues - antes, 50);
+            assert!(sistema._existe_usuario(accounts.bob).is_err());
+
+            //Solo el admin configura la tarifa.
+            assert_eq!(sistema.configurar_tarifa_registro(0).unwrap_err(), ErrorSistema::NoEsAdmin);
+        }
+
+        #[ink::test]
+        fn test_darse_de_baja_con_publicaciones() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            let mut sistema = Sistema::new(true);
+            let libros = sistema.crear_categoria(String::from("Libros"), None).unwrap();
+            sistema.registrar_usuario(String::from("Alice"), String::from("Surname"), String::from("alice.email"), Rol::Vendedor).unwrap();
+            aprobar_vendedor(&mut sistema, accounts.alice);
+            let libro = sistema.crear_publicacion(String::from("Libro"), String::from("Usado"), 100, 1, libros).unwrap();
+
+            assert_eq!(sistema.darse_de_baja().unwrap_err(), ErrorSistema::TienePublicaciones);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(sistema.darse_de_baja().unwrap_err(), ErrorSistema::UsuarioNoExiste);
+
+            //Una vez que retira su única publicación sí puede darse de baja.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            sistema.retirar_publicacion(libro).unwrap();
+            sistema.darse_de_baja().unwrap();
+        }
+
+        #[ink::test]
+        fn test_registro_solo_invitacion() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            let mut sistema = Sistema::new(true);
+            sistema.configurar_modo_registro(ModoRegistro::SoloInvitacion).unwrap();
+            let hash = sistema.hash_codigo_invitacion(String::from("bienvenido-bob"), accounts.bob);
+            sistema.crear_invitacion(hash).unwrap();
+
+            //El código está atado a Bob: si Charlie lo copia de la transacción de Bob no le sirve.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            let error = sistema.registrar_usuario_con_invitacion(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Comprador, String::from("bienvenido-bob")).unwrap_err();
+            assert_eq!(error, ErrorSistema::InvitacionInvalida);
+
+            //Sin invitación o con un código equivocado no se puede registrar.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let error = sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador).unwrap_err();
+            assert_eq!(error, ErrorSistema::InvitacionRequerida);
+            let error = sistema.registrar_usuario_con_invitacion(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador, String::from("otro")).unwrap_err();
+            assert_eq!(error, ErrorSistema::InvitacionInvalida);
+
+            sistema.registrar_usuario_con_invitacion(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador, String::from("bienvenido-bob")).unwrap();
+            assert!(sistema._existe_usuario(accounts.bob).is_ok());
+            //La invitación es de un solo uso.
+            assert!(sistema.invitaciones.get(hash).is_none());
+
+            //Y solo pueden invitar usuarios registrados.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(sistema.crear_invitacion(hash).unwrap_err(), ErrorSistema::UsuarioNoExiste);
+        }
+
+        #[ink::test]
+        fn test_registro_con_aprobacion() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            let mut sistema = Sistema::new(true);
+            sistema.configurar_modo_registro(ModoRegistro::ConAprobacion).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Vendedor).unwrap();
+            let error = sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Vendedor).unwrap_err();
+            assert_eq!(error, ErrorSistema::RegistroPendiente);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Comprador).unwrap();
+
+            //Quedan pendientes hasta que el admin los aprueba.
+            assert!(sistema._existe_usuario(accounts.bob).is_err());
+            assert_eq!(sistema.listar_registros_pendientes(0, 10).len(), 2);
+            assert_eq!(sistema.listar_registros_pendientes(1, 1)[0].id, accounts.charlie);
+            assert_eq!(sistema.aprobar_registro(accounts.bob).unwrap_err(), ErrorSistema::NoEsAdmin);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            sistema.aprobar_registro(accounts.bob).unwrap();
+            assert_eq!(sistema.listar_registros_pendientes(0, 10)[0].id, accounts.charlie);
+            sistema.rechazar_registro(accounts.charlie).unwrap();
+            assert!(sistema._existe_usuario(accounts.bob).is_ok());
+            assert!(sistema.get_solicitud_vendedor(accounts.bob).is_ok());
+            assert!(sistema._existe_usuario(accounts.charlie).is_err());
+            assert!(sistema.listar_registros_pendientes(0, 10).is_empty());
+            assert_eq!(sistema.rechazar_registro(accounts.charlie).unwrap_err(), ErrorSistema::SolicitudNoExiste);
+        }
+
+        #[ink::test]
+        fn test_solicitud_vendedor() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            //Alice es la admin y nombra a Charlie como moderador.
+            let mut sistema = Sistema::new(true);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Comprador).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            sistema.asignar_rol(accounts.charlie, Rol::Moderador).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Comprador).unwrap();
+            assert!(sistema.listar_solicitudes_vendedor(0, 10).is_empty());
+            sistema.agregar_rol(Rol::Vendedor).unwrap();
+            assert_eq!(sistema.listar_solicitudes_vendedor(0, 10), Vec::from([accounts.bob]));
+            assert!(matches!(sistema.es_vendedor(), Ok(false)));
+
+            //Bob no puede aprobarse a sí mismo.
+            assert_eq!(sistema.aprobar_vendedor(accounts.bob).unwrap_err(), ErrorSistema::NoEsModerador);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.rechazar_vendedor(accounts.bob, String::from("Faltan datos")).unwrap();
+            let solicitud = sistema.get_solicitud_vendedor(accounts.bob).unwrap();
+            assert_eq!(solicitud.estado, EstadoSolicitud::Rechazada(String::from("Faltan datos")));
+            assert_eq!(solicitud.revisor, Some(accounts.charlie));
+            assert!(sistema.listar_solicitudes_vendedor(0, 10).is_empty());
+            //Una solicitud ya resuelta no se puede volver a resolver.
+            assert_eq!(sistema.aprobar_vendedor(accounts.bob).unwrap_err(), ErrorSistema::SolicitudNoExiste);
+
+            //Bob vuelve a pedir la aprobación y esta vez se la dan.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.reenviar_solicitud_vendedor().unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.aprobar_vendedor(accounts.bob).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(matches!(sistema.es_vendedor(), Ok(true)));
+            assert!(sistema.get_perfil_vendedor(accounts.bob).is_ok());
+
+            //Si la admin le saca el rol, volver a pedirlo no lo deja vendiendo: tiene que aprobarse otra vez.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            sistema.quitar_rol(accounts.bob, Rol::Vendedor).unwrap();
+            assert_eq!(sistema.get_solicitud_vendedor(accounts.bob).unwrap_err(), ErrorSistema::SolicitudNoExiste);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.agregar_rol(Rol::Vendedor).unwrap();
+            assert!(matches!(sistema.es_vendedor(), Ok(false)));
+            assert_eq!(sistema.get_solicitud_vendedor(accounts.bob).unwrap().estado, EstadoSolicitud::Pendiente);
+            assert_eq!(sistema.listar_solicitudes_vendedor(0, 10), Vec::from([accounts.bob]));
+        }
+
+        #[ink::test]
+        fn test_crear_categoria() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            //Alice despliega el contrato, por lo que es la admin.
+            let mut sistema = Sistema::new(true);
+            let raiz = sistema.crear_categoria(String::from("Electrónica"), None).unwrap();
+            let hija = sistema.crear_categoria(String::from("Celulares"), Some(raiz)).unwrap();
+
+            assert_eq!(sistema.get_categoria(hija).unwrap().padre, Some(raiz));
+            assert_eq!(sistema.listar_subcategorias(None).len(), 1);
+            assert_eq!(sistema.listar_subcategorias(Some(raiz))[0].id, hija);
+
+            //El padre tiene que existir.
+            let error = sistema.crear_categoria(String::from("Huérfana"), Some(42)).unwrap_err();
+            assert_eq!(error, ErrorSistema::CategoriaNoExiste);
+
+            //Bob no es admin.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let error = sistema.crear_categoria(String::from("Ropa"), None).unwrap_err();
+            assert_eq!(error, ErrorSistema::NoEsAdmin);
+        }
+
+        #[ink::test]
+        fn test_listar_por_categoria() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            let mut sistema = Sistema::new(true);
+            let libros = sistema.crear_categoria(String::from("Libros"), None).unwrap();
+            let ropa = sistema.crear_categoria(String::from("Ropa"), None).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            sistema.registrar_usuario(String::from("Bob"), String::from("Surname"), String::from("bob.email"), Rol::Vendedor).unwrap();
+            aprobar_vendedor(&mut sistema, accounts.bob);
+            for i in 0..5 {
+                sistema.crear_publicacion(format!("Libro {}", i), String::from("Usado"), 100, 1, libros).unwrap();
+            }
+            sistema.crear_publicacion(String::from("Remera"), String::from("Nueva"), 50, 3, ropa).unwrap();
+
+            //La publicación tiene que referenciar una categoría válida.
+            let error = sistema.crear_publicacion(String::from("X"), String::from("X"), 1, 1, 42).unwrap_err();
+            assert_eq!(error, ErrorSistema::CategoriaNoExiste);
+
+            assert_eq!(sistema.listar_por_categoria(libros, 0, 2).unwrap().len(), 2);
+            assert_eq!(sistema.listar_por_categoria(libros, 2, 2).unwrap().len(), 1);
+            assert!(sistema.listar_por_categoria(libros, 3, 2).unwrap().is_empty());
+            assert_eq!(sistema.listar_por_categoria(ropa, 0, 10).unwrap()[0].nombre, String::from("Remera"));
+
+            //Un comprador no puede publicar.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            sistema.registrar_usuario(String::from("Charlie"), String::from("Surname"), String::from("charlie.email"), Rol::Comprador).unwrap();
+            let error = sistema.crear_publicacion(String::from("X"), String::from("X"), 1, 1, ropa).unwrap_err();
+            assert_eq!(error, ErrorSistema::NoEsVendedor);
+        }
     }
 
 